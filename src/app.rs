@@ -1,7 +1,9 @@
-use crate::screens::{help, home, rerun, results, runner, test};
+use crate::screens::{builder, help, home, rerun, results, runner, stats, test};
 use crate::service::{testservice, configservice, ioservice};
+use crate::models::{schedule, test::{ResultModel, TestModel}};
 
 use std::io;
+use std::path::PathBuf;
 use tui::{
     backend::Backend,
     Frame
@@ -9,8 +11,9 @@ use tui::{
 use crossterm::event::{Event, KeyCode, KeyEvent}; 
 
 use rusqlite::Connection;
+use rust_i18n::t;
 
-#[derive(Clone, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum ScreenType {
     Home,
     Tests,
@@ -21,6 +24,13 @@ pub enum ScreenType {
     Runner,
     Importer,
     Config,
+    History,
+    Pause,
+    Retry,
+    Review,
+    Stats,
+    Builder,
+    Validator,
 }
 
 pub struct App {
@@ -33,30 +43,42 @@ pub struct App {
     rerun: rerun::Rerun,
     help: help::Help,
     runner: runner::Runner,
+    stats: stats::Stats,
+    builder: builder::Builder,
     conn: Connection,
 }
 
 impl App {
-    pub fn new() -> Self {
-        let conn = testservice::init_conn_and_populate();
+    pub fn new(conn: Connection) -> Self {
         let tests_to_do = testservice::get_to_do(&conn);
+        let tests_paused = testservice::get_paused(&conn);
         let tests_finished = testservice::get_finished(&conn);
+        let tags = testservice::get_exam_tags(&conn);
         let default_locale = configservice::get_locale();
         // TODO should this be from config as well?
         let all_locales = vec![
             (String::from("en"), String::from("English")), 
             (String::from("pl"), String::from("Polski")), 
         ];
+        let mut tests = test::Tests::new(tests_to_do, default_locale.clone());
+        tests.mark_paused(&tests_paused);
+        tests.set_tags(tags.clone());
+        let mut rerun = rerun::Rerun::new(tests_finished, default_locale.clone());
+        rerun.set_tags(tags);
+        let mut home = home::Home::new(default_locale.clone());
+        home.set_due(testservice::count_due(&conn, schedule::today()));
         App { 
             is_finished: false,
             locale: default_locale.clone(),
             current_screen: ScreenType::Home,
-            home: home::Home::new(default_locale.clone()),
-            tests: test::Tests::new(tests_to_do, default_locale.clone()),
+            home,
+            tests,
             results: results::Results::new(None, default_locale.clone()),
-            rerun: rerun::Rerun::new(tests_finished, default_locale.clone()),
+            rerun,
             help: help::Help::new(default_locale.clone(), all_locales),
             runner: runner::Runner::new(None, default_locale.clone()),
+            stats: stats::Stats::new(Default::default(), default_locale.clone()),
+            builder: builder::Builder::new(vec![], vec![], default_locale.clone()),
             conn,
         }
     }
@@ -70,19 +92,32 @@ impl App {
             ScreenType::Help => self.help.draw(f),
             ScreenType::Runner => self.runner.draw(f),
             ScreenType::Importer => self.help.draw(f),
+            ScreenType::Validator => self.help.draw(f),
             ScreenType::Config => self.help.draw(f),
+            ScreenType::History => self.rerun.draw(f),
+            ScreenType::Pause => self.runner.draw(f),
+            ScreenType::Retry => self.runner.draw(f),
+            ScreenType::Review => self.home.draw(f),
+            ScreenType::Stats => self.stats.draw(f),
+            ScreenType::Builder => self.builder.draw(f),
             ScreenType::Quit => self.is_finished = true,
         }
     }
 
+    pub fn on_tick(&mut self) {
+        if self.current_screen == ScreenType::Runner {
+            self.runner.on_tick();
+            self.save_submitted();
+        }
+    }
+
     pub fn is_done(&self) -> bool {
         self.is_finished
     }
 
     pub fn on_terminal_event(&mut self, event: Event) -> Result<(), io::Error> {
-        match event {
-            Event::Key(ev) => self.on_key_event(ev)?,
-            _ => {}
+        if let Event::Key(ev) = event {
+            self.on_key_event(ev)?
         }
         Ok(())
     }
@@ -90,77 +125,187 @@ impl App {
     fn on_key_event(&mut self, event: KeyEvent) -> Result<(), io::Error> {
         match event.code {
             KeyCode::Char('q') | KeyCode::Char('Q') => {
-                // will not work while test is on
+                // will not work while test is on, the runner may need it for typed answers
                 if !self.runner.is_running() {
                     self.is_finished = true
+                } else {
+                    self.handle_key_code(event.code)?
                 }
             },
             // make sure the home screen is always cleared when comming back to it
             KeyCode::Char('b') | KeyCode::Char('B') => {
                 self.home.first_render = true;
-                self.handle_key_code(KeyCode::Char('b'))?
+                self.handle_key_code(event.code)?
             }
             other_code => self.handle_key_code(other_code)?
         }
         Ok(())
     }
 
+    fn open_runner(&mut self, test_id: String, origin: ScreenType) {
+        let test_model = testservice::get_test_by_id(&self.conn, test_id.clone());
+        self.new_runner(test_model, origin);
+        self.runner.set_progress(testservice::get_progress(&self.conn, test_id.clone()));
+    }
+
+    // asks again only the questions answered wrong in the result
+    fn open_retry(&mut self, result: ResultModel, origin: ScreenType) {
+        let Some(mut test_model) = testservice::get_retry_test(&self.conn, &result) else {
+            self.current_screen = origin;
+            return;
+        };
+        test_model.title = format!("{} {}", test_model.title, t!("tests.retry", locale = &self.locale));
+        self.new_runner(Some(test_model), origin);
+        self.runner.retry_of = result.attempt;
+    }
+
+    // one session with the questions of every test that are due today
+    fn open_review(&mut self) {
+        let title = t!("review.title", locale = &self.locale).to_string();
+        match testservice::get_due_test(&self.conn, title, schedule::today()) {
+            Some(test_model) => self.new_runner(Some(test_model), ScreenType::Home),
+            None => {
+                self.home.show_review_empty();
+                self.current_screen = ScreenType::Home;
+            },
+        }
+    }
+
+    // the custom test is saved first so it can be taken again from the list of tests
+    fn open_custom(&mut self) {
+        let title = self.builder.title();
+        let Some(test_id) = testservice::save_custom_test(&self.conn, self.builder.custom(), title) else {
+            return;
+        };
+        self.tests.update_items(testservice::get_to_do(&self.conn));
+        self.tests.mark_paused(&testservice::get_paused(&self.conn));
+        self.update_tags();
+        self.open_runner(test_id, ScreenType::Tests);
+    }
+
+    // the result is saved as soon as the summary is shown, so quitting from it keeps the attempt
+    fn save_submitted(&mut self) {
+        let Some(r) = self.runner.take_submitted() else {
+            return;
+        };
+        if r.is_review() {
+            testservice::save_review(&self.conn, &r);
+            self.home.set_due(testservice::count_due(&self.conn, schedule::today()));
+            return;
+        }
+        // practicing does not count as taking the test
+        if !r.practice {
+            testservice::set_finished(&self.conn, r.id.clone());
+        }
+        let to_do = testservice::get_to_do(&self.conn);
+        let finished = testservice::get_finished(&self.conn);
+        self.tests.update_items(to_do);
+        self.rerun.update_items(finished);
+        self.runner.set_attempt(testservice::save_result(&self.conn, r));
+        self.tests.mark_paused(&testservice::get_paused(&self.conn));
+        self.home.set_due(testservice::count_due(&self.conn, schedule::today()));
+    }
+
+    fn new_runner(&mut self, test_model: Option<TestModel>, origin: ScreenType) {
+        self.runner = runner::Runner::new(test_model, self.locale.clone());
+        self.runner.origin = origin;
+        self.runner.match_options = configservice::get_match_options();
+        self.runner.shuffle = configservice::get_shuffle();
+        (self.runner.question_limit, self.runner.time_limit) = configservice::get_time_limits();
+        self.runner.grade_scale = configservice::get_grade_scale();
+        self.current_screen = ScreenType::Runner;
+    }
+
     fn handle_key_code(&mut self, code: KeyCode) -> Result<(), io::Error> {
         match self.current_screen {
-            ScreenType::Home => self.current_screen = self.home.handle_key_code(code),
+            ScreenType::Home => {
+                match self.home.handle_key_code(code) {
+                    ScreenType::Review => self.open_review(),
+                    ScreenType::Stats => {
+                        self.stats = stats::Stats::new(testservice::get_stats(&self.conn), self.locale.clone());
+                        self.current_screen = ScreenType::Stats;
+                    },
+                    ScreenType::Builder => {
+                        let exams = testservice::get_exams(&self.conn);
+                        self.builder = builder::Builder::new(exams, testservice::get_tags(&self.conn), self.locale.clone());
+                        self.current_screen = ScreenType::Builder;
+                    },
+                    screen => self.current_screen = screen,
+                }
+            },
+            ScreenType::Stats => self.current_screen = self.stats.handle_key_code(code),
+            ScreenType::Builder => {
+                match self.builder.handle_key_code(code) {
+                    ScreenType::Runner => self.open_custom(),
+                    // the questions are counted again only when the chosen tests or tags change
+                    ScreenType::Builder => if self.builder.take_changed() {
+                        let available = testservice::count_custom_pool(&self.conn, self.builder.custom());
+                        self.builder.set_available(available);
+                    },
+                    screen => self.current_screen = screen,
+                }
+            },
             ScreenType::Tests => {
                 let (screen, test_id) = self.tests.handle_key_code(code);
                 match screen {
-                    ScreenType::Runner => {
-                        let test_model = testservice::get_test_by_id(&self.conn, test_id);
-                        self.runner = runner::Runner::new(test_model, self.locale.clone());
-                        self.runner.origin = ScreenType::Tests;
-                        self.current_screen = ScreenType::Runner;
-                    },
+                    ScreenType::Runner => self.open_runner(test_id, ScreenType::Tests),
                     ScreenType::Home => self.current_screen = ScreenType::Home,
                     _ => self.current_screen = screen 
                 }
             },
-            ScreenType::Results => self.current_screen = self.results.handle_key_code(code),
+            ScreenType::Results => {
+                match self.results.handle_key_code(code) {
+                    ScreenType::Retry => {
+                        if let Some(r) = self.results.result() {
+                            self.open_retry(r, ScreenType::Results);
+                        }
+                    },
+                    screen => self.current_screen = screen,
+                }
+            },
             ScreenType::Rerun => {
                 let (screen, test_id) = self.rerun.handle_key_code(code);
                 match screen {
                     ScreenType::Home => self.current_screen = ScreenType::Home,
-                    ScreenType::Runner => {
-                        let test_model = testservice::get_test_by_id(&self.conn, test_id);
-                        self.runner = runner::Runner::new(test_model, self.locale.clone());
-                        self.runner.origin = ScreenType::Rerun;
-                        self.current_screen = ScreenType::Runner;
-                    },
+                    ScreenType::Runner => self.open_runner(test_id, ScreenType::Rerun),
                     ScreenType::Results => {
-                        let result = testservice::get_result_by_id(&self.conn, test_id);
+                        let result = if self.rerun.is_history() {
+                            testservice::get_result_by_attempt(&self.conn, test_id)
+                        } else {
+                            testservice::get_result_by_id(&self.conn, test_id)
+                        };
                         self.results = results::Results::new(result, self.locale.clone());
                         self.current_screen = ScreenType::Results;
                     },
+                    ScreenType::History => {
+                        let attempts = testservice::get_attempts(&self.conn, test_id);
+                        self.rerun.show_history(attempts);
+                        self.current_screen = ScreenType::Rerun;
+                    },
                     _ => self.current_screen = screen
                 }
             },
             ScreenType::Runner => {
-                // TODO handle case when test is finished but option 'back' was chosen instead of
-                // 'details'
                 let (screen, result) = self.runner.handle_key_code(code);
-                match result.clone() {
-                    Some(r) => {
-                        let id = r.id.clone();
-                        testservice::set_finished(&self.conn, id);
-                        let to_do = testservice::get_to_do(&self.conn);
-                        let finished = testservice::get_finished(&self.conn);
-                        self.tests.update_items(to_do);
-                        self.rerun.update_items(finished);
-                        testservice::save_result(&self.conn, r);
-                    }
-                    None => (),
+                self.save_submitted();
+                // leaving a running test keeps it to be resumed later, retries and reviews are dropped once the user confirmed it
+                if (screen == ScreenType::Pause || screen == ScreenType::Quit)
+                    && let Some(progress) = self.runner.pause()
+                    && progress.result.retry_of.is_none() && !progress.result.is_review() {
+                    testservice::save_progress(&self.conn, &progress);
+                    self.tests.mark_paused(&testservice::get_paused(&self.conn));
                 }
                 match screen {
+                    ScreenType::Pause => self.current_screen = self.runner.origin.clone(),
                     ScreenType::Results => {
-                        self.results = results::Results::new(result.clone(), self.locale.clone());
+                        self.results = results::Results::new(result, self.locale.clone());
                         self.current_screen = ScreenType::Results
                     },
+                    ScreenType::Retry => {
+                        if let Some(r) = result {
+                            self.open_retry(r, self.runner.origin.clone());
+                        }
+                    },
                     _ => self.current_screen = screen
                 }
             },
@@ -179,6 +324,14 @@ impl App {
                         self.help.import_results = ioservice::import_test_files(&self.locale, &self.conn);
                         let to_do = testservice::get_to_do(&self.conn);
                         self.tests.update_items(to_do);
+                        self.tests.mark_paused(&testservice::get_paused(&self.conn));
+                        self.update_tags();
+                        self.help.state = 4;
+                        self.current_screen = ScreenType::Help;
+                    },
+                    ScreenType::Validator => {
+                        let import_dir = PathBuf::from(ioservice::IMPORT_DIR);
+                        (self.help.import_results, _) = ioservice::validate_test_files(&self.locale, &[import_dir]);
                         self.help.state = 4;
                         self.current_screen = ScreenType::Help;
                     },
@@ -190,6 +343,13 @@ impl App {
         Ok(())
     }
 
+    // new tests may bring new tags to filter the lists with
+    fn update_tags(&mut self) {
+        let tags = testservice::get_exam_tags(&self.conn);
+        self.tests.set_tags(tags.clone());
+        self.rerun.set_tags(tags);
+    }
+
     fn update_locale(&mut self, locale: String) {
         self.locale = locale;
         self.home.update_locale(self.locale.clone());
@@ -197,6 +357,8 @@ impl App {
         self.results.locale = self.locale.clone();
         self.rerun.locale = self.locale.clone();
         self.runner.locale = self.locale.clone();
+        self.stats.locale = self.locale.clone();
+        self.builder.locale = self.locale.clone();
         self.help.locale = self.locale.clone();
         configservice::save_locale(self.locale.clone());
     }
@@ -0,0 +1,122 @@
+use std::path::PathBuf;
+
+pub const USAGE: &str = "Usage: cli-ltr [OPTIONS]
+       cli-ltr validate [PATH]...
+       cli-ltr export [--yaml] [--exam ID] [DIR]
+
+Commands:
+  validate      check test files, or directories of them, without importing anything
+                (the import directory when no path is given)
+  export        write every test, or only the one with --exam ID, to DIR as JSON,
+                or YAML with --yaml, in a form the import reads back
+                (./export when no directory is given)
+
+Options:
+  --db <PATH>   use the database file at PATH (created on first run)
+  --demo        run on an in-memory database seeded with example tests
+  -h, --help    print this help";
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Args {
+    pub db: Option<PathBuf>,
+    pub demo: bool,
+    pub help: bool,
+    // paths to check when only validating test files
+    pub validate: Option<Vec<PathBuf>>,
+    // directory to export the tests to, none for the default one, whether as YAML instead of JSON
+    // and the id of the only test to export
+    pub export: Option<Option<PathBuf>>,
+    pub yaml: bool,
+    pub exam: Option<String>,
+}
+
+pub fn parse<I: Iterator<Item = String>>(mut args: I) -> Result<Args, String> {
+    let mut parsed = Args::default();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--db" => match args.next() {
+                Some(path) => parsed.db = Some(PathBuf::from(path)),
+                None => return Err(String::from("--db requires a path")),
+            },
+            "--demo" => parsed.demo = true,
+            "-h" | "--help" => parsed.help = true,
+            "validate" if parsed.validate.is_none() && parsed.export.is_none() => parsed.validate = Some(vec![]),
+            "export" if parsed.validate.is_none() && parsed.export.is_none() => parsed.export = Some(None),
+            "--yaml" if parsed.export.is_some() => parsed.yaml = true,
+            "--exam" if parsed.export.is_some() => match args.next() {
+                Some(id) => parsed.exam = Some(id),
+                None => return Err(String::from("--exam requires a test id")),
+            },
+            path if !path.starts_with('-') && let Some(paths) = parsed.validate.as_mut() => paths.push(PathBuf::from(path)),
+            path if !path.starts_with('-') && let Some(dir @ None) = parsed.export.as_mut() => *dir = Some(PathBuf::from(path)),
+            other => return Err(format!("unknown argument: {}", other)),
+        }
+    }
+    Ok(parsed)
+}
+
+
+#[cfg(test)]
+mod units {
+    use super::*;
+
+    fn to_args(args: &[&str]) -> std::vec::IntoIter<String> {
+        args.iter().map(|a| a.to_string()).collect::<Vec<String>>().into_iter()
+    }
+
+    #[test]
+    fn test_parse_empty() {
+        let result = parse(to_args(&[]));
+
+        assert_eq!(result, Ok(Args::default()));
+    }
+
+    #[test]
+    fn test_parse_db_and_demo() {
+        let expected = Args { db: Some(PathBuf::from("/tmp/x.db")), demo: true, ..Args::default() };
+
+        let result = parse(to_args(&["--db", "/tmp/x.db", "--demo"]));
+
+        assert_eq!(result, Ok(expected));
+    }
+
+    #[test]
+    fn test_parse_db_without_path() {
+        let result = parse(to_args(&["--db"]));
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_parse_validate() {
+        let without_paths = parse(to_args(&["validate"]));
+        let with_paths = parse(to_args(&["validate", "a.txt", "tests/"]));
+
+        assert_eq!(without_paths.unwrap().validate, Some(vec![]));
+        assert_eq!(with_paths.unwrap().validate, Some(vec![PathBuf::from("a.txt"), PathBuf::from("tests/")]));
+        assert!(parse(to_args(&["a.txt"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_export() {
+        let default_dir = parse(to_args(&["export"])).unwrap();
+        let yaml_dir = parse(to_args(&["--db", "x.db", "export", "--yaml", "out"])).unwrap();
+        let single = parse(to_args(&["export", "--exam", "3"])).unwrap();
+
+        assert_eq!(default_dir.export, Some(None));
+        assert!(!default_dir.yaml);
+        assert_eq!(yaml_dir.export, Some(Some(PathBuf::from("out"))));
+        assert!(yaml_dir.yaml);
+        assert_eq!((single.export, single.exam), (Some(None), Some(String::from("3"))));
+        assert!(parse(to_args(&["export", "a", "b"])).is_err());
+        assert!(parse(to_args(&["export", "--exam"])).is_err());
+        assert!(parse(to_args(&["--yaml"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_unknown() {
+        let result = parse(to_args(&["--nope"]));
+
+        assert_eq!(result, Err(String::from("unknown argument: --nope")));
+    }
+}
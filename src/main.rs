@@ -1,20 +1,58 @@
 mod terminal;
 mod app;
+mod cli;
 
 mod screens;
 mod ui;
 mod models;
 mod service;
-//use service::ioservice;
+use service::{configservice, ioservice, testservice};
 
-#[macro_use]
-extern crate rust_i18n;
+use std::{env, path::PathBuf, process};
 
 rust_i18n::i18n!("locales", fallback = "en");
 
 fn main() -> Result<(), std::io::Error> {
-    terminal::run()
-    //ioservice::import_test_files("pl");
-    //Ok(())
-}
+    let args = match cli::parse(env::args().skip(1)) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, cli::USAGE);
+            process::exit(2);
+        }
+    };
+    if args.help {
+        println!("{}", cli::USAGE);
+        return Ok(());
+    }
+    if let Some(mut paths) = args.validate {
+        if paths.is_empty() {
+            paths.push(PathBuf::from(ioservice::IMPORT_DIR));
+        }
+        let (logs, invalid) = ioservice::validate_test_files(&configservice::get_locale(), &paths);
+        logs.iter().for_each(|l| println!("{}", l));
+        process::exit(if invalid > 0 { 1 } else { 0 });
+    }
+
+    let conn = if args.demo {
+        testservice::init_conn_and_populate()
+    } else {
+        let path = configservice::get_db_path(args.db);
+        match testservice::init_conn(&path) {
+            Ok(conn) => conn,
+            Err(e) => {
+                eprintln!("Could not open database {:?}: {}", path, e);
+                process::exit(1);
+            }
+        }
+    };
 
+    if let Some(dir) = args.export {
+        let dir = dir.unwrap_or_else(|| PathBuf::from(ioservice::EXPORT_DIR));
+        let format = if args.yaml { ioservice::Format::Yaml } else { ioservice::Format::Json };
+        let (logs, failed) = ioservice::export_test_files(&configservice::get_locale(), &conn, &dir, format, args.exam.as_deref());
+        logs.iter().for_each(|l| println!("{}", l));
+        process::exit(if failed > 0 { 1 } else { 0 });
+    }
+
+    terminal::run(conn)
+}
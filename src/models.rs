@@ -1,2 +1,6 @@
 pub mod test;
-
+pub mod matching;
+pub mod schedule;
+pub mod stats;
+pub mod scoring;
+pub mod builder;
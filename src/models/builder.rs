@@ -0,0 +1,90 @@
+use crate::models::test::{QuestionModel, TestModel};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum QuestionOrder {
+    // questions keep the order of the tests they come from
+    #[default]
+    InOrder,
+    Shuffled,
+}
+
+impl QuestionOrder {
+    pub fn next(&self) -> Self {
+        match self {
+            QuestionOrder::InOrder => QuestionOrder::Shuffled,
+            QuestionOrder::Shuffled => QuestionOrder::InOrder,
+        }
+    }
+}
+
+// questions picked from several tests and tags, saved as a test of its own
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CustomTest {
+    // ids of the tests whose questions are all taken
+    pub exams: Vec<String>,
+    // questions with any of the tags are taken as well
+    pub tags: Vec<String>,
+    pub count: usize,
+    pub order: QuestionOrder,
+}
+
+impl CustomTest {
+    pub fn is_empty(&self) -> bool {
+        self.exams.is_empty() && self.tags.is_empty()
+    }
+
+    // every question of the pool is kept and each attempt draws the count from them,
+    // a question picked twice, or copied into an earlier custom test, is asked once
+    pub fn to_model(&self, title: String, pool: Vec<QuestionModel>) -> TestModel {
+        let mut questions: Vec<QuestionModel> = vec![];
+        for q in pool {
+            if !questions.iter().any(|p| is_same(p, &q)) {
+                questions.push(q);
+            }
+        }
+        questions.iter_mut().enumerate().for_each(|(idx, q)| q.number = idx + 1);
+
+        let mut model = TestModel::new(String::new(), title, questions);
+        model.shuffle = Some(self.order == QuestionOrder::Shuffled);
+        model.sample_size = (self.count < model.questions.len()).then_some(self.count);
+        model
+    }
+}
+
+fn is_same(a: &QuestionModel, b: &QuestionModel) -> bool {
+    a.question == b.question && a.kind == b.kind && a.answers == b.answers && a.gaps == b.gaps
+}
+
+
+#[cfg(test)]
+mod units {
+    use super::*;
+    use crate::models::test::QuestionKind;
+
+    fn question(id: &str) -> QuestionModel {
+        let mut q = QuestionModel::new(format!("q{}", id), QuestionKind::Single, vec![String::from("a")], vec![0]);
+        q.id = id.to_string();
+        q
+    }
+
+    #[test]
+    fn test_questions_picked_twice_asked_once() {
+        let custom = CustomTest { exams: vec![String::from("1")], tags: vec![String::from("verbs")], count: 2, order: QuestionOrder::Shuffled };
+
+        let result = custom.to_model(String::from("mixed"), vec![question("1"), question("2"), question("3"), question("2")]);
+
+        assert_eq!(result.questions.iter().map(|q| q.number).collect::<Vec<usize>>(), vec![1, 2, 3]);
+        assert_eq!(result.sample_size, Some(2));
+        assert_eq!(result.shuffle, Some(true));
+    }
+
+    #[test]
+    fn test_small_pool_asked_whole() {
+        let custom = CustomTest { exams: vec![String::from("1")], count: 10, ..CustomTest::default() };
+
+        let result = custom.to_model(String::from("mixed"), vec![question("1")]);
+
+        assert_eq!(result.sample_size, None);
+        assert_eq!(result.shuffle, Some(false));
+    }
+}
@@ -0,0 +1,173 @@
+use unicode_normalization::UnicodeNormalization;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct MatchOptions {
+    pub ignore_case: bool,
+    pub ignore_diacritics: bool,
+    // number of single character edits still accepted as a typo
+    pub max_typos: usize,
+}
+
+impl Default for MatchOptions {
+    fn default() -> Self {
+        MatchOptions { ignore_case: true, ignore_diacritics: true, max_typos: 1 }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum MatchKind {
+    Wrong,
+    Exact,
+    Typo,
+}
+
+impl MatchKind {
+    pub fn code(&self) -> &'static str {
+        match self {
+            MatchKind::Wrong => "",
+            MatchKind::Exact => "EXACT",
+            MatchKind::Typo => "TYPO",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Self {
+        match code {
+            "EXACT" => MatchKind::Exact,
+            "TYPO" => MatchKind::Typo,
+            _ => MatchKind::Wrong,
+        }
+    }
+}
+
+// returns the index of the best matching accepted answer, exact matches win over typos
+pub fn match_typed(text: &str, accepted: &[String], options: &MatchOptions) -> (Option<usize>, MatchKind) {
+    let given = normalize(text, options);
+    if given.is_empty() {
+        return (None, MatchKind::Wrong);
+    }
+
+    let normalized = accepted.iter()
+        .map(|a| normalize(a, options))
+        .collect::<Vec<String>>();
+    if let Some(idx) = normalized.iter().position(|a| *a == given) {
+        return (Some(idx), MatchKind::Exact);
+    }
+
+    normalized.iter()
+        .enumerate()
+        .map(|(idx, a)| (idx, levenshtein(a, &given), allowed_typos(a, options)))
+        .filter(|(_, distance, allowed)| distance <= allowed)
+        .min_by_key(|(_, distance, _)| *distance)
+        .map_or((None, MatchKind::Wrong), |(idx, _, _)| (Some(idx), MatchKind::Typo))
+}
+
+// short words would match almost anything with a typo, so one edit is allowed per four characters
+fn allowed_typos(accepted: &str, options: &MatchOptions) -> usize {
+    options.max_typos.min(accepted.chars().count() / 4)
+}
+
+fn normalize(text: &str, options: &MatchOptions) -> String {
+    let mut result = text.split_whitespace().collect::<Vec<&str>>().join(" ");
+    if options.ignore_case {
+        result = result.to_lowercase();
+    }
+    if options.ignore_diacritics {
+        result = strip_diacritics(&result);
+    }
+    result
+}
+
+fn strip_diacritics(text: &str) -> String {
+    text.nfd()
+        .filter(|c| !('\u{0300}'..='\u{036f}').contains(c))
+        // letters with a stroke do not decompose
+        .map(|c| match c {
+            'ł' => 'l',
+            'Ł' => 'L',
+            'ø' => 'o',
+            'Ø' => 'O',
+            'đ' => 'd',
+            'Đ' => 'D',
+            _ => c,
+        })
+        .collect()
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.iter().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+
+#[cfg(test)]
+mod units {
+    use super::*;
+
+    fn accepted(items: &[&str]) -> Vec<String> {
+        items.iter().map(|i| i.to_string()).collect()
+    }
+
+    #[test]
+    fn test_levenshtein() {
+        assert_eq!(levenshtein("went", "went"), 0);
+        assert_eq!(levenshtein("went", "wnet"), 2);
+        assert_eq!(levenshtein("written", "writen"), 1);
+        assert_eq!(levenshtein("", "abc"), 3);
+    }
+
+    #[test]
+    fn test_match_exact_ignoring_case_and_spaces() {
+        let result = match_typed("  Have   BEEN ", &accepted(&["have been"]), &MatchOptions::default());
+
+        assert_eq!(result, (Some(0), MatchKind::Exact));
+    }
+
+    #[test]
+    fn test_match_ignoring_diacritics() {
+        let result = match_typed("zolw", &accepted(&["żółw"]), &MatchOptions::default());
+
+        assert_eq!(result, (Some(0), MatchKind::Exact));
+    }
+
+    #[test]
+    fn test_match_strict_diacritics() {
+        let options = MatchOptions { ignore_diacritics: false, max_typos: 0, ..MatchOptions::default() };
+
+        let result = match_typed("zolw", &accepted(&["żółw"]), &options);
+
+        assert_eq!(result, (None, MatchKind::Wrong));
+    }
+
+    #[test]
+    fn test_match_typo() {
+        let result = match_typed("writen", &accepted(&["wrote", "written"]), &MatchOptions::default());
+
+        assert_eq!(result, (Some(1), MatchKind::Typo));
+    }
+
+    #[test]
+    fn test_short_words_need_exact_match() {
+        let result = match_typed("do", &accepted(&["go"]), &MatchOptions::default());
+
+        assert_eq!(result, (None, MatchKind::Wrong));
+    }
+
+    #[test]
+    fn test_empty_answer() {
+        let result = match_typed("   ", &accepted(&["went"]), &MatchOptions::default());
+
+        assert_eq!(result, (None, MatchKind::Wrong));
+    }
+}
@@ -0,0 +1,94 @@
+use crate::models::matching::MatchKind;
+use crate::models::test::AnswerModel;
+
+use chrono::{Datelike, Local};
+
+// review state of a single question, scheduled with the SM-2 algorithm
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReviewState {
+    pub ease: f64,
+    // days until the next review
+    pub interval: u32,
+    // correct answers in a row
+    pub repetitions: u32,
+    // day of the next review, counted in days from the common era like today()
+    pub due: i32,
+}
+
+impl Default for ReviewState {
+    fn default() -> Self {
+        ReviewState { ease: 2.5, interval: 0, repetitions: 0, due: 0 }
+    }
+}
+
+impl ReviewState {
+    // quality goes from 0 (no answer) to 5 (perfect answer), below 3 the question is learned again
+    pub fn next(&self, quality: u8, today: i32) -> ReviewState {
+        let quality = quality.min(5);
+        let (interval, repetitions) = if quality < 3 {
+            (1, 0)
+        } else {
+            let interval = match self.repetitions {
+                0 => 1,
+                1 => 6,
+                _ => (self.interval as f64 * self.ease).round() as u32,
+            };
+            (interval, self.repetitions + 1)
+        };
+        let miss = (5 - quality) as f64;
+        let ease = (self.ease + 0.1 - miss * (0.08 + miss * 0.02)).max(1.3);
+        ReviewState { ease, interval, repetitions, due: today + interval as i32 }
+    }
+}
+
+// how well the question was answered, typos and partial credit lower the quality
+pub fn quality(answer: &AnswerModel) -> u8 {
+    if answer.timed_out || !answer.is_answered() {
+        return 0;
+    }
+    match (answer.is_correct, &answer.matched) {
+        (true, MatchKind::Typo) => 4,
+        (true, _) => 5,
+        _ if answer.score > 0.0 => 2,
+        _ => 1,
+    }
+}
+
+pub fn today() -> i32 {
+    Local::now().date_naive().num_days_from_ce()
+}
+
+
+#[cfg(test)]
+mod units {
+    use super::*;
+
+    #[test]
+    fn test_intervals_grow_with_correct_answers() {
+        let first = ReviewState::default().next(5, 100);
+        let second = first.next(5, 101);
+        let third = second.next(5, 107);
+
+        assert_eq!((first.interval, first.due), (1, 101));
+        assert_eq!((second.interval, second.due), (6, 107));
+        assert_eq!(third.interval, 16);
+        assert_eq!(third.repetitions, 3);
+    }
+
+    #[test]
+    fn test_wrong_answer_starts_over() {
+        let learned = ReviewState { ease: 2.5, interval: 15, repetitions: 3, due: 100 };
+
+        let result = learned.next(1, 100);
+
+        assert_eq!((result.interval, result.repetitions, result.due), (1, 0, 101));
+        assert!(result.ease < learned.ease);
+    }
+
+    #[test]
+    fn test_ease_has_a_floor() {
+        let result = ReviewState { ease: 1.3, ..ReviewState::default() }.next(0, 0);
+
+        assert_eq!(result.ease, 1.3);
+    }
+}
@@ -0,0 +1,157 @@
+use crate::models::test::AnswerModel;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum GradeScale {
+    #[default]
+    None,
+    // A to F in steps of ten percent
+    Letters,
+    // Polish school grades from 1 to 6
+    Polish,
+    // lowest percent needed for every grade, the highest band first
+    Custom(Vec<(f64, String)>),
+}
+
+impl GradeScale {
+    // 'letters', 'polish' or custom bands like '90=A, 75=B, 0=C', anything else means no grades
+    pub fn from_code(code: &str) -> Self {
+        match code.trim().to_lowercase().as_str() {
+            "letters" => GradeScale::Letters,
+            "polish" => GradeScale::Polish,
+            _ => Self::parse_bands(code),
+        }
+    }
+
+    fn parse_bands(code: &str) -> Self {
+        let bands = code.split(',')
+            .map(|band| {
+                let (min, grade) = band.split_once('=')?;
+                let grade = grade.trim();
+                (!grade.is_empty()).then_some((min.trim().trim_end_matches('%').parse::<f64>().ok()?, grade.to_string()))
+            })
+            .collect::<Option<Vec<(f64, String)>>>();
+        match bands {
+            Some(mut bands) if !bands.is_empty() => {
+                bands.sort_by(|a, b| b.0.total_cmp(&a.0));
+                GradeScale::Custom(bands)
+            },
+            _ => GradeScale::None,
+        }
+    }
+
+    fn bands(&self) -> Vec<(f64, String)> {
+        let bands: &[(f64, &str)] = match self {
+            GradeScale::None => &[],
+            GradeScale::Letters => &[(90.0, "A"), (80.0, "B"), (70.0, "C"), (60.0, "D"), (50.0, "E"), (0.0, "F")],
+            GradeScale::Polish => &[(98.0, "6"), (90.0, "5"), (75.0, "4"), (50.0, "3"), (30.0, "2"), (0.0, "1")],
+            GradeScale::Custom(bands) => return bands.clone(),
+        };
+        bands.iter().map(|(min, grade)| (*min, grade.to_string())).collect()
+    }
+
+    pub fn grade(&self, percent: f64) -> Option<String> {
+        self.bands().into_iter()
+            .find(|(min, _)| percent >= *min)
+            .map(|(_, grade)| grade)
+    }
+}
+
+// how a finished attempt was scored, kept with the attempt so changing the rules does not rewrite history
+#[derive(Clone, Debug, PartialEq)]
+pub struct Grade {
+    pub points: f64,
+    pub max_points: f64,
+    pub passed: Option<bool>,
+    pub grade: Option<String>,
+}
+
+impl Grade {
+    pub fn percent(&self) -> f64 {
+        if self.max_points <= 0.0 {
+            return 0.0;
+        }
+        self.points * 100.0 / self.max_points
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Scoring {
+    // share of the question points taken away for a wrong answer, unanswered questions cost nothing
+    pub negative: f64,
+    // percent needed to pass, no threshold means the test cannot be failed
+    pub pass_threshold: Option<f64>,
+    pub scale: GradeScale,
+}
+
+impl Scoring {
+    pub fn answer_points(&self, answer: &AnswerModel) -> f64 {
+        if answer.score > 0.0 {
+            answer.score * answer.points
+        } else if answer.is_answered() {
+            -self.negative * answer.points
+        } else {
+            0.0
+        }
+    }
+
+    // negative marking can take points away but the total never goes below zero
+    pub fn grade(&self, answers: &[AnswerModel]) -> Grade {
+        let points = answers.iter().map(|a| self.answer_points(a)).sum::<f64>().max(0.0);
+        let max_points = answers.iter().map(|a| a.points).sum::<f64>();
+        let mut grade = Grade { points, max_points, passed: None, grade: None };
+        let percent = grade.percent();
+        grade.passed = self.pass_threshold.map(|t| percent >= t);
+        grade.grade = self.scale.grade(percent);
+        grade
+    }
+}
+
+
+#[cfg(test)]
+mod units {
+    use super::*;
+    use crate::models::test::{QuestionKind, QuestionModel};
+
+    fn answers(points: &[f64], given: &[Vec<usize>]) -> Vec<AnswerModel> {
+        points.iter()
+            .zip(given.iter())
+            .map(|(p, g)| {
+                let mut q = QuestionModel::new(String::from("q"), QuestionKind::Single, vec![String::from("a"), String::from("b")], vec![0]);
+                q.points = *p;
+                AnswerModel::new(&q, g.clone(), 1)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_weighted_points() {
+        let answers = answers(&[1.0, 3.0, 1.0], &[vec![0], vec![0], vec![1]]);
+
+        let result = Scoring::default().grade(&answers);
+
+        assert_eq!((result.points, result.max_points), (4.0, 5.0));
+        assert_eq!(result.percent(), 80.0);
+        assert_eq!(result.passed, None);
+    }
+
+    #[test]
+    fn test_negative_marking_skips_unanswered() {
+        let answers = answers(&[2.0, 2.0, 2.0], &[vec![0], vec![1], vec![]]);
+        let scoring = Scoring { negative: 0.5, pass_threshold: Some(50.0), scale: GradeScale::Letters };
+
+        let result = scoring.grade(&answers);
+
+        assert_eq!(result.points, 1.0);
+        assert_eq!(result.passed, Some(false));
+        assert_eq!(result.grade, Some(String::from("F")));
+    }
+
+    #[test]
+    fn test_grade_scales() {
+        assert_eq!(GradeScale::Polish.grade(92.0), Some(String::from("5")));
+        assert_eq!(GradeScale::Letters.grade(100.0), Some(String::from("A")));
+        assert_eq!(GradeScale::None.grade(100.0), None);
+        assert_eq!(GradeScale::from_code("0=fail, 60%=pass").grade(75.0), Some(String::from("pass")));
+        assert_eq!(GradeScale::from_code("60=pass, x"), GradeScale::None);
+    }
+}
@@ -0,0 +1,172 @@
+use chrono::{DateTime, Duration, Local, NaiveDate};
+
+// score of one finished attempt
+#[derive(Clone, Debug)]
+pub struct AttemptScore {
+    pub exam: String,
+    pub finished: i64,
+    // sum of the partial scores of all answers
+    pub score: f64,
+    pub count: usize,
+    // weighted points kept with the attempt, older attempts have none
+    pub points: Option<f64>,
+    pub max_points: Option<f64>,
+}
+
+impl AttemptScore {
+    pub fn percent(&self) -> u64 {
+        if let (Some(points), Some(max_points)) = (self.points, self.max_points)
+            && max_points > 0.0 {
+            return (points * 100.0 / max_points).round() as u64;
+        }
+        if self.count == 0 {
+            return 0;
+        }
+        (self.score * 100.0 / self.count as f64).round() as u64
+    }
+
+    fn day(&self) -> Option<NaiveDate> {
+        DateTime::from_timestamp(self.finished, 0).map(|d| d.with_timezone(&Local).date_naive())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct QuestionStats {
+    pub exam: String,
+    pub question: String,
+    pub answered: usize,
+    // share of the score that was missed, from 0 to 1
+    pub error_rate: f64,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TagStats {
+    pub tag: String,
+    pub answered: usize,
+    // share of the score that was earned, from 0 to 1
+    pub score_rate: f64,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExamStats {
+    pub exam: String,
+    pub attempts: usize,
+    pub best: u64,
+    pub last: u64,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct StatsModel {
+    // finished attempts of every day, the oldest day first
+    pub attempts_per_day: Vec<(String, u64)>,
+    // score in percent of every attempt, the oldest first
+    pub score_trend: Vec<u64>,
+    // seconds spent on a question
+    pub average_time: f64,
+    pub hardest: Vec<QuestionStats>,
+    pub exams: Vec<ExamStats>,
+    pub tags: Vec<TagStats>,
+}
+
+impl StatsModel {
+    // attempts have to be ordered from the oldest one
+    pub fn new(attempts: &[AttemptScore], hardest: Vec<QuestionStats>, tags: Vec<TagStats>, average_time: f64, today: NaiveDate, days: i64) -> Self {
+        StatsModel {
+            attempts_per_day: per_day(attempts, today, days),
+            score_trend: attempts.iter().map(|a| a.percent()).collect(),
+            average_time,
+            hardest,
+            exams: per_exam(attempts),
+            tags,
+        }
+    }
+
+    pub fn attempt_count(&self) -> usize {
+        self.score_trend.len()
+    }
+}
+
+// the last days up to today, days without attempts are kept so the chart shows the gaps
+fn per_day(attempts: &[AttemptScore], today: NaiveDate, days: i64) -> Vec<(String, u64)> {
+    (0..days).rev()
+        .map(|back| today - Duration::days(back))
+        .map(|day| {
+            let count = attempts.iter().filter(|a| a.day() == Some(day)).count() as u64;
+            (day.format("%d.%m").to_string(), count)
+        })
+        .collect()
+}
+
+// exams in the order they were first taken
+fn per_exam(attempts: &[AttemptScore]) -> Vec<ExamStats> {
+    let mut exams: Vec<ExamStats> = vec![];
+    for a in attempts {
+        match exams.iter_mut().find(|e| e.exam == a.exam) {
+            Some(e) => {
+                e.attempts += 1;
+                e.best = e.best.max(a.percent());
+                e.last = a.percent();
+            },
+            None => exams.push(ExamStats { exam: a.exam.clone(), attempts: 1, best: a.percent(), last: a.percent() }),
+        }
+    }
+    exams
+}
+
+
+#[cfg(test)]
+mod units {
+    use super::*;
+
+    fn attempt(exam: &str, finished: NaiveDate, score: f64) -> AttemptScore {
+        let finished = finished.and_hms_opt(12, 0, 0).unwrap().and_local_timezone(Local).unwrap().timestamp();
+        AttemptScore { exam: exam.to_string(), finished, score, count: 4, points: None, max_points: None }
+    }
+
+    #[test]
+    fn test_attempts_per_day_keep_empty_days() {
+        let today = NaiveDate::from_ymd_opt(2025, 3, 10).unwrap();
+        let attempts = vec![
+            attempt("a", NaiveDate::from_ymd_opt(2025, 3, 8).unwrap(), 1.0),
+            attempt("a", today, 2.0),
+            attempt("b", today, 3.0),
+        ];
+
+        let result = StatsModel::new(&attempts, vec![], vec![], 0.0, today, 3);
+
+        assert_eq!(result.attempts_per_day, vec![
+            (String::from("08.03"), 1),
+            (String::from("09.03"), 0),
+            (String::from("10.03"), 2),
+        ]);
+        assert_eq!(result.score_trend, vec![25, 50, 75]);
+    }
+
+    #[test]
+    fn test_best_and_last_score_per_exam() {
+        let today = NaiveDate::from_ymd_opt(2025, 3, 10).unwrap();
+        let attempts = vec![
+            attempt("a", today, 3.0),
+            attempt("b", today, 1.0),
+            attempt("a", today, 2.0),
+        ];
+
+        let result = StatsModel::new(&attempts, vec![], vec![], 0.0, today, 1);
+
+        assert_eq!(result.exams, vec![
+            ExamStats { exam: String::from("a"), attempts: 2, best: 75, last: 50 },
+            ExamStats { exam: String::from("b"), attempts: 1, best: 25, last: 25 },
+        ]);
+    }
+
+    #[test]
+    fn test_weighted_points_preferred() {
+        let mut weighted = attempt("a", NaiveDate::from_ymd_opt(2025, 3, 10).unwrap(), 1.0);
+        weighted.points = Some(3.0);
+        weighted.max_points = Some(4.0);
+
+        assert_eq!(weighted.percent(), 75);
+        weighted.max_points = Some(0.0);
+        assert_eq!(weighted.percent(), 25);
+    }
+}
@@ -1,8 +1,28 @@
+use super::matching::{self, MatchKind, MatchOptions};
+use super::scoring::Grade;
+
+use rand::{seq::SliceRandom, Rng};
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct TestModel {
-    pub id: String, 
-    pub title: String, 
+    pub id: String,
+    pub title: String,
     pub questions: Vec<QuestionModel>,
+    // overrides the global shuffle option when set
+    pub shuffle: Option<bool>,
+    // limits in seconds, they override the global ones when set, 0 means no limit
+    pub question_limit: Option<u64>,
+    pub time_limit: Option<u64>,
+    // share of the points taken for a wrong answer, percent needed to pass and the grade scale code
+    pub negative: Option<f64>,
+    pub pass_threshold: Option<f64>,
+    pub grade_scale: Option<String>,
+    // questions drawn for every attempt, all of them when not set
+    pub sample_size: Option<usize>,
+    // questions with a tag that have to be among the drawn ones
+    pub quotas: Vec<(String, usize)>,
+    // categories and levels of the whole test, questions can have their own tags as well
+    pub tags: Vec<String>,
 }
 
 impl TestModel {
@@ -11,37 +31,152 @@ impl TestModel {
         title: String,
         questions: Vec<QuestionModel>
     ) -> Self {
-        TestModel { id, title, questions, }
+        TestModel {
+            id, title, questions,
+            shuffle: None, question_limit: None, time_limit: None,
+            negative: None, pass_threshold: None, grade_scale: None,
+            sample_size: None, quotas: vec![], tags: vec![],
+        }
+    }
+
+    // indices of the questions drawn for one attempt in test order, the tag quotas are filled first
+    pub fn draw_sample<R: Rng + ?Sized>(&self, size: usize, rng: &mut R) -> Vec<usize> {
+        let size = size.min(self.questions.len());
+        let mut drawn: Vec<usize> = vec![];
+        for (tag, count) in self.quotas.iter() {
+            let mut pool = (0..self.questions.len())
+                .filter(|idx| !drawn.contains(idx) && self.questions[*idx].tags.contains(tag))
+                .collect::<Vec<usize>>();
+            pool.shuffle(rng);
+            drawn.extend(pool.into_iter().take((*count).min(size - drawn.len())));
+        }
+        let mut rest = (0..self.questions.len())
+            .filter(|idx| !drawn.contains(idx))
+            .collect::<Vec<usize>>();
+        rest.shuffle(rng);
+        drawn.extend(rest.into_iter().take(size - drawn.len()));
+        drawn.sort();
+        drawn
+    }
+
+    // the same test reduced to the questions with the given numbers
+    pub fn with_questions(&self, numbers: &[usize]) -> TestModel {
+        let mut model = self.clone();
+        model.questions.retain(|q| numbers.contains(&q.number));
+        model
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum QuestionKind {
+    // exactly one answer is correct
+    Single,
+    // select all that apply, any number of answers can be correct
+    Multiple,
+    // the answer is typed in, every answer of the question is an accepted spelling
+    Typed,
+    // the question text has numbered gaps like {1}, each of them is a dropdown or a typed field
+    Cloze,
+}
+
+impl QuestionKind {
+    pub fn code(&self) -> &'static str {
+        match self {
+            QuestionKind::Single => "SINGLE",
+            QuestionKind::Multiple => "MULTIPLE",
+            QuestionKind::Typed => "TYPED",
+            QuestionKind::Cloze => "CLOZE",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Self {
+        match code {
+            "MULTIPLE" => QuestionKind::Multiple,
+            "TYPED" => QuestionKind::Typed,
+            "CLOZE" => QuestionKind::Cloze,
+            _ => QuestionKind::Single,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct GapModel {
+    // typed gaps accept every answer, the others are shown as a dropdown
+    pub typed: bool,
+    pub answers: Vec<String>,
+    pub correct: Vec<usize>,
+}
+
+impl GapModel {
+    pub fn new(typed: bool, answers: Vec<String>, correct: Vec<usize>) -> Self {
+        GapModel { typed, answers, correct }
+    }
+
+    pub fn marker(number: usize) -> String {
+        format!("{{{}}}", number)
     }
 }
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct QuestionModel {
+    // database id, empty until the question is stored
+    pub id: String,
+    // position of the question in the test as imported, kept when questions are shuffled
+    pub number: usize,
     pub question: String,
+    pub kind: QuestionKind,
     pub answers: Vec<String>,
-    pub correct: u8,
+    pub correct: Vec<usize>,
+    // only used by cloze questions, gap {1} is the first element
+    pub gaps: Vec<GapModel>,
+    // why the correct answer is correct, empty when the test has none
+    pub explanation: String,
+    // weight of the question in the score
+    pub points: f64,
+    pub tags: Vec<String>,
 }
 
 impl QuestionModel {
     pub fn new(
         question: String,
+        kind: QuestionKind,
         answers: Vec<String>,
-        correct: u8,
+        correct: Vec<usize>,
     ) -> Self {
-        QuestionModel {question, answers, correct}
+        QuestionModel {id: String::new(), number: 0, question, kind, answers, correct, gaps: vec![], explanation: String::new(), points: 1.0, tags: vec![]}
+    }
+
+    pub fn cloze(question: String, gaps: Vec<GapModel>) -> Self {
+        QuestionModel {id: String::new(), number: 0, question, kind: QuestionKind::Cloze, answers: vec![], correct: vec![], gaps, explanation: String::new(), points: 1.0, tags: vec![]}
     }
 
-    pub fn is_correct(&self, answer: Option<usize>) -> bool {
-        match answer {
-	    None => return false,
-	    Some(a) => {
-		if a == usize::from(self.correct) {
-		    true
-		} else {
-		    false
-		}
-	    }
-	}
+    // only the exact set of correct answers counts as correct
+    pub fn is_correct(&self, given: &[usize]) -> bool {
+        if self.kind == QuestionKind::Typed {
+            return !given.is_empty() && given.iter().all(|g| self.correct.contains(g));
+        }
+        let mut sorted = given.to_vec();
+        sorted.sort();
+        sorted.dedup();
+        let mut correct = self.correct.clone();
+        correct.sort();
+        !sorted.is_empty() && sorted == correct
+    }
+
+    // partial credit between 0 and 1, every wrong pick cancels a correct one
+    // cloze questions are scored per gap by AnswerModel::cloze
+    pub fn score(&self, given: &[usize]) -> f64 {
+        if self.correct.is_empty() {
+            return 0.0;
+        }
+        match self.kind {
+            QuestionKind::Single | QuestionKind::Typed | QuestionKind::Cloze => if self.is_correct(given) { 1.0 } else { 0.0 },
+            QuestionKind::Multiple => {
+                let hits = given.iter().filter(|g| self.correct.contains(g)).count() as f64;
+                let misses = given.iter().filter(|g| !self.correct.contains(g)).count() as f64;
+                ((hits - misses) / self.correct.len() as f64).max(0.0)
+            }
+        }
     }
 }
 
@@ -51,46 +186,371 @@ pub struct ResultModel {
     pub title: String,
     pub answers: Vec<AnswerModel>,
     pub total_time: u64,
+    // answers were checked one by one in practice mode
+    pub practice: bool,
+    // id of the stored attempt and of the attempt it retried the wrong answers of
+    pub attempt: Option<String>,
+    pub retry_of: Option<String>,
+    // points, pass and grade, only known once the attempt is finished
+    pub grade: Option<Grade>,
 }
 
 impl ResultModel {
     pub fn new(
         id: String,
         title: String,
-        answers: Vec<AnswerModel>, 
+        answers: Vec<AnswerModel>,
         total_time: u64
         ) -> Self {
-        ResultModel { 
+        ResultModel {
             id,
             title,
-            answers, 
-            total_time 
+            answers,
+            total_time,
+            practice: false,
+            attempt: None,
+            retry_of: None,
+            grade: None,
         }
     }
+
+    pub fn score(&self) -> usize {
+        self.answers.iter().filter(|a| a.is_correct).count()
+    }
+
+    // numbers of the questions answered wrong or not answered at all
+    pub fn wrong_numbers(&self) -> Vec<usize> {
+        self.answers.iter()
+            .filter(|a| !a.is_correct)
+            .map(|a| a.number)
+            .collect()
+    }
+
+    // a review session mixes questions of many tests, it does not belong to any of them
+    pub fn is_review(&self) -> bool {
+        self.id.is_empty()
+    }
+
+    // only the answers of a test can be retried, review sessions are rebuilt from what is due
+    pub fn can_retry(&self) -> bool {
+        !self.is_review() && !self.wrong_numbers().is_empty()
+    }
+
+    pub fn mode(&self) -> &'static str {
+        if self.practice { "PRACTICE" } else { "EXAM" }
+    }
+}
+
+// a test paused in the middle, the result holds the answers given so far
+// and the time spent on the whole test
+#[derive(Clone, Debug)]
+pub struct ProgressModel {
+    pub result: ResultModel,
+    pub question_order: Vec<usize>,
+    // time already spent on the question shown when pausing
+    pub question_time: u64,
+    // positions in the asked order of the flagged questions and of the one shown when pausing,
+    // attempts paused before it was kept continue with the first question without an answer
+    pub flagged: Vec<usize>,
+    pub current: Option<usize>,
+}
+
+impl ProgressModel {
+    pub fn new(result: ResultModel, question_order: Vec<usize>, question_time: u64) -> Self {
+        ProgressModel { result, question_order, question_time, flagged: vec![], current: None }
+    }
 }
 
 #[derive(Clone, Debug)]
 pub struct AnswerModel {
+    pub question_id: String,
+    pub number: usize,
     pub question: String,
+    pub kind: QuestionKind,
     pub answers: Vec<String>,
-    pub correct: u8,
-    pub given: Option<usize>,
+    pub correct: Vec<usize>,
+    // chosen answer indices, empty when the question was not answered
+    pub given: Vec<usize>,
+    // the text typed in for typed questions and how close it was to an accepted answer
+    pub typed: String,
+    pub matched: MatchKind,
+    // one entry per gap of a cloze question
+    pub gaps: Vec<GapAnswer>,
+    pub explanation: String,
+    pub points: f64,
+    // submitted automatically because the time limit ran out
+    pub timed_out: bool,
     pub is_correct: bool,
+    pub score: f64,
     pub time: u64,
 }
 
 impl AnswerModel {
     pub fn new(
-	question: String,
-        answers: Vec<String>,
-	correct: u8,
-	given: Option<usize>,
-	is_correct: bool,
-	time: u64,
+        question: &QuestionModel,
+        given: Vec<usize>,
+        time: u64,
     ) -> Self {
-	AnswerModel { question, answers, correct, given, is_correct, time}
+        let is_correct = question.is_correct(&given);
+        AnswerModel {
+            question_id: question.id.clone(),
+            number: question.number,
+            question: question.question.clone(),
+            kind: question.kind.clone(),
+            answers: question.answers.clone(),
+            correct: question.correct.clone(),
+            is_correct,
+            score: question.score(&given),
+            given,
+            typed: String::new(),
+            matched: if is_correct { MatchKind::Exact } else { MatchKind::Wrong },
+            gaps: vec![],
+            explanation: question.explanation.clone(),
+            points: question.points,
+            timed_out: false,
+            time,
+        }
+    }
+
+    // every gap is scored on its own, the question is correct when all of them are
+    pub fn cloze(question: &QuestionModel, gaps: Vec<GapAnswer>, time: u64) -> Self {
+        let mut answer = AnswerModel::new(question, vec![], time);
+        let correct = gaps.iter().filter(|g| g.is_correct()).count();
+        answer.is_correct = !gaps.is_empty() && correct == gaps.len();
+        answer.score = if gaps.is_empty() { 0.0 } else { correct as f64 / gaps.len() as f64 };
+        answer.matched = if answer.is_correct { MatchKind::Exact } else { MatchKind::Wrong };
+        answer.gaps = gaps;
+        answer
+    }
+
+    pub fn is_answered(&self) -> bool {
+        !self.given.is_empty() || !self.typed.is_empty() || self.gaps.iter().any(|g| g.given.is_some() || !g.typed.is_empty())
+    }
+
+    // the question text with every gap replaced by the given answer
+    pub fn filled_question(&self) -> String {
+        fill_gaps(&self.question, &self.gaps.iter().map(|g| Some(g.text())).collect::<Vec<Option<String>>>())
+    }
+
+    pub fn typed(question: &QuestionModel, text: String, options: &MatchOptions, time: u64) -> Self {
+        let (idx, matched) = matching::match_typed(&text, &question.answers, options);
+        Self::with_match(question, text, idx, matched, time)
+    }
+
+    pub fn with_match(question: &QuestionModel, text: String, idx: Option<usize>, matched: MatchKind, time: u64) -> Self {
+        let mut answer = AnswerModel::new(question, idx.into_iter().collect(), time);
+        answer.typed = text;
+        answer.matched = matched;
+        answer
     }
 }
 
+#[derive(Clone, Debug, PartialEq)]
+pub struct GapAnswer {
+    pub gap: GapModel,
+    pub given: Option<usize>,
+    pub typed: String,
+    pub matched: MatchKind,
+}
 
+impl GapAnswer {
+    pub fn choice(gap: &GapModel, given: Option<usize>) -> Self {
+        let correct = given.is_some_and(|g| gap.correct.contains(&g));
+        GapAnswer {
+            gap: gap.clone(),
+            given,
+            typed: String::new(),
+            matched: if correct { MatchKind::Exact } else { MatchKind::Wrong },
+        }
+    }
+
+    pub fn typed(gap: &GapModel, text: String, options: &MatchOptions) -> Self {
+        let (given, matched) = matching::match_typed(&text, &gap.answers, options);
+        GapAnswer { gap: gap.clone(), given, typed: text, matched }
+    }
 
+    pub fn with_match(gap: &GapModel, given: Option<usize>, text: String, matched: MatchKind) -> Self {
+        GapAnswer { gap: gap.clone(), given, typed: text, matched }
+    }
+
+    pub fn is_correct(&self) -> bool {
+        self.matched != MatchKind::Wrong
+    }
+
+    // what the user put into the gap
+    pub fn text(&self) -> String {
+        if self.gap.typed {
+            return self.typed.clone();
+        }
+        self.given
+            .and_then(|g| self.gap.answers.get(g).cloned())
+            .unwrap_or_default()
+    }
+}
+
+// tags are compared in lower case, like 'Verbs, B2', a repeated tag keeps its first place
+pub fn parse_tags(value: &str) -> Vec<String> {
+    let mut tags: Vec<String> = vec![];
+    for tag in value.split(',').map(|t| t.trim().to_lowercase()).filter(|t| !t.is_empty()) {
+        if !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    tags
+}
+
+// quotas like 'verbs=5, idioms=2', invalid entries are skipped
+pub fn parse_quotas(value: &str) -> Vec<(String, usize)> {
+    value.split(',')
+        .filter_map(|q| q.split_once('='))
+        .filter_map(|(tag, count)| Some((tag.trim().to_lowercase(), count.trim().parse::<usize>().ok()?)))
+        .filter(|(tag, _)| !tag.is_empty())
+        .collect()
+}
+
+pub fn encode_quotas(quotas: &[(String, usize)]) -> String {
+    quotas.iter()
+        .map(|(tag, count)| format!("{}={}", tag, count))
+        .collect::<Vec<String>>()
+        .join(",")
+}
+
+// replaces every {n} marker with the n-th value, gaps without a value are left blank
+pub fn fill_gaps(text: &str, values: &[Option<String>]) -> String {
+    let mut filled = text.to_string();
+    for (idx, value) in values.iter().enumerate() {
+        let replacement = match value {
+            Some(v) if !v.is_empty() => format!("[{}]", v),
+            _ => format!("[{}: ___]", idx + 1),
+        };
+        filled = filled.replace(&GapModel::marker(idx + 1), &replacement);
+    }
+    filled
+}
+
+
+#[cfg(test)]
+mod units {
+    use super::*;
+
+    fn multiple() -> QuestionModel {
+        let answers = vec![String::from("a"), String::from("b"), String::from("c"), String::from("d")];
+        QuestionModel::new(String::from("q"), QuestionKind::Multiple, answers, vec![0, 2])
+    }
+
+    #[test]
+    fn test_multiple_exact_match() {
+        let q = multiple();
+
+        assert!(q.is_correct(&[2, 0]));
+        assert_eq!(q.score(&[2, 0]), 1.0);
+    }
+
+    #[test]
+    fn test_multiple_partial_credit() {
+        let q = multiple();
+
+        assert!(!q.is_correct(&[0]));
+        assert_eq!(q.score(&[0]), 0.5);
+    }
+
+    #[test]
+    fn test_multiple_wrong_pick_cancels_correct() {
+        let q = multiple();
+
+        assert_eq!(q.score(&[0, 1]), 0.0);
+        assert_eq!(q.score(&[0, 2, 3]), 0.5);
+    }
+
+    #[test]
+    fn test_typed_with_typo_is_correct() {
+        let answers = vec![String::from("written"), String::from("writ")];
+        let q = QuestionModel::new(String::from("q"), QuestionKind::Typed, answers, vec![0, 1]);
+
+        let result = AnswerModel::typed(&q, String::from("writen"), &MatchOptions::default(), 3);
+
+        assert!(result.is_correct);
+        assert_eq!(result.matched, MatchKind::Typo);
+        assert_eq!(result.given, vec![0]);
+        assert_eq!(result.score, 1.0);
+    }
+
+    #[test]
+    fn test_cloze_scored_per_gap() {
+        let gaps = vec![
+            GapModel::new(false, vec![String::from("was"), String::from("were")], vec![1]),
+            GapModel::new(true, vec![String::from("dumb")], vec![0]),
+        ];
+        let q = QuestionModel::cloze(String::from("I wish you {1} so {2}."), gaps.clone());
+        let answers = vec![
+            GapAnswer::choice(&gaps[0], Some(1)),
+            GapAnswer::typed(&gaps[1], String::from("smart"), &MatchOptions::default()),
+        ];
+
+        let result = AnswerModel::cloze(&q, answers, 4);
+
+        assert!(!result.is_correct);
+        assert_eq!(result.score, 0.5);
+        assert_eq!(result.filled_question(), "I wish you [were] so [smart].");
+    }
+
+    #[test]
+    fn test_fill_gaps_blank() {
+        let result = fill_gaps("{1} and {2}", &[Some(String::from("this")), None]);
+
+        assert_eq!(result, "[this] and [2: ___]");
+    }
+
+    #[test]
+    fn test_unanswered() {
+        let q = multiple();
+
+        assert!(!q.is_correct(&[]));
+        assert_eq!(q.score(&[]), 0.0);
+    }
+
+    #[test]
+    fn test_retry_keeps_wrong_questions() {
+        let mut questions = vec![multiple(), multiple(), multiple()];
+        questions.iter_mut().enumerate().for_each(|(idx, q)| q.number = idx + 1);
+        let model = TestModel::new(String::from("1"), String::from("t"), questions.clone());
+        let answers = vec![
+            AnswerModel::new(&questions[0], vec![0, 2], 1),
+            AnswerModel::new(&questions[1], vec![0], 1),
+            AnswerModel::new(&questions[2], vec![], 1),
+        ];
+        let result = ResultModel::new(String::from("1"), String::from("t"), answers, 3);
+
+        let retry = model.with_questions(&result.wrong_numbers());
+
+        assert_eq!(result.wrong_numbers(), vec![2, 3]);
+        assert_eq!(retry.questions.iter().map(|q| q.number).collect::<Vec<usize>>(), vec![2, 3]);
+    }
+
+    #[test]
+    fn test_sample_fills_quotas_first() {
+        let mut questions = (0..10).map(|_| multiple()).collect::<Vec<QuestionModel>>();
+        questions[7].tags = vec![String::from("verbs")];
+        questions[9].tags = vec![String::from("verbs")];
+        let mut model = TestModel::new(String::from("1"), String::from("t"), questions);
+        model.quotas = parse_quotas("Verbs=2, broken");
+        let mut rng = rand::rngs::mock::StepRng::new(0, 1);
+
+        let result = model.draw_sample(3, &mut rng);
+
+        assert_eq!(result.len(), 3);
+        assert!(result.contains(&7) && result.contains(&9));
+        assert!(result.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(model.draw_sample(20, &mut rng).len(), 10);
+    }
+
+    #[test]
+    fn test_parse_and_encode_quotas() {
+        let quotas = parse_quotas("verbs = 5, B2=1, x=y");
+
+        assert_eq!(quotas, vec![(String::from("verbs"), 5), (String::from("b2"), 1)]);
+        assert_eq!(encode_quotas(&quotas), "verbs=5,b2=1");
+        assert_eq!(parse_tags(" Verbs, ,b2"), vec![String::from("verbs"), String::from("b2")]);
+        assert_eq!(parse_tags("verbs, B2, Verbs"), vec![String::from("verbs"), String::from("b2")]);
+    }
+}
@@ -5,3 +5,5 @@ pub mod rerun;
 pub mod help;
 pub mod runner;
 
+pub mod stats;
+pub mod builder;
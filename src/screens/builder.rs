@@ -0,0 +1,190 @@
+use crate::app::ScreenType;
+use crate::models::builder::{CustomTest, QuestionOrder};
+use crate::ui::{layout, menu::Menu, navbar, navbar::NavType};
+
+use std::mem;
+
+use tui::{
+    backend::Backend,
+    layout::{Rect},
+    style::{Modifier, Style},
+    text::{Span, Spans},
+    widgets::Clear,
+    Frame,
+};
+use crossterm::event::{KeyCode};
+use rust_i18n::t;
+
+pub struct Builder {
+    pub first_render: bool,
+    pub locale: String,
+    exams: Vec<(String, String)>,
+    tags: Vec<(String, usize)>,
+    // one flag for every row, tests first and then tags
+    chosen: Vec<bool>,
+    list: Menu,
+    custom: CustomTest,
+    // questions the chosen tests and tags have together
+    available: usize,
+    // tests or tags were chosen since the questions were last counted
+    changed: bool,
+}
+
+impl Builder {
+    pub fn new(exams: Vec<(String, String)>, tags: Vec<(String, usize)>, locale: String) -> Self {
+        let chosen = vec![false; exams.len() + tags.len()];
+        let custom = CustomTest { count: 10, ..CustomTest::default() };
+        let mut builder = Builder { first_render: true, locale, exams, tags, chosen, list: Menu::new(vec![]), custom, available: 0, changed: false };
+        builder.update_list();
+        builder
+    }
+
+    pub fn draw<B: Backend>(&mut self, f: &mut Frame<B>) {
+        if self.first_render {
+            self.first_render = false;
+            f.render_widget(Clear, f.size());
+            return;
+        }
+
+        let background = layout::get_background();
+        f.render_widget(background, f.size());
+
+        let layout = layout::get_header_navbar_layout(f.size(), 3, 3);
+
+        self.render_header(f, layout[0]);
+        self.render_navbar(f, layout[1]);
+        if self.chosen.is_empty() {
+            self.render_empty_items(f, layout[2]);
+        } else {
+            self.render_body(f, layout[2]);
+        }
+    }
+
+    pub fn handle_key_code(&mut self, code: KeyCode) -> ScreenType {
+        match code {
+            KeyCode::Char('b') | KeyCode::Char('B') => return ScreenType::Home,
+            KeyCode::Up => self.list.previous(),
+            KeyCode::Down => self.list.next(),
+            KeyCode::Char(' ') => self.toggle(),
+            KeyCode::Char('+') => self.custom.count += 1,
+            KeyCode::Char('-') => self.custom.count = self.custom.count.saturating_sub(1).max(1),
+            KeyCode::Char('o') | KeyCode::Char('O') => self.custom.order = self.custom.order.next(),
+            KeyCode::Enter if !self.custom.is_empty() && self.available > 0 => return ScreenType::Runner,
+            _ => {}
+        }
+        ScreenType::Builder
+    }
+
+    pub fn custom(&self) -> &CustomTest {
+        &self.custom
+    }
+
+    pub fn take_changed(&mut self) -> bool {
+        mem::take(&mut self.changed)
+    }
+
+    pub fn set_available(&mut self, available: usize) {
+        self.available = available;
+    }
+
+    // the custom test is named after the tests and tags it was made from
+    pub fn title(&self) -> String {
+        let exams = self.exams.iter()
+            .filter(|e| self.custom.exams.contains(&e.0))
+            .map(|e| e.1.split_once("] ").map(|(_, name)| name).unwrap_or(&e.1).to_string());
+        let tags = self.custom.tags.iter().map(|tag| format!("#{}", tag));
+        let sources = exams.chain(tags).collect::<Vec<String>>().join(", ");
+        t!("builder.name", locale = &self.locale, sources = sources).to_string()
+    }
+
+    fn toggle(&mut self) {
+        let Some(idx) = self.list.state.selected() else {
+            return;
+        };
+        self.chosen[idx] = !self.chosen[idx];
+        self.custom.exams = self.exams.iter()
+            .zip(self.chosen.iter())
+            .filter(|(_, chosen)| **chosen)
+            .map(|(e, _)| e.0.clone())
+            .collect();
+        self.custom.tags = self.tags.iter()
+            .zip(self.chosen[self.exams.len()..].iter())
+            .filter(|(_, chosen)| **chosen)
+            .map(|(tag, _)| tag.0.clone())
+            .collect();
+        self.changed = true;
+        self.update_list();
+    }
+
+    fn update_list(&mut self) {
+        let selected = self.list.state.selected();
+        let exams = self.exams.iter().map(|e| e.1.clone());
+        let tags = self.tags.iter().map(|(tag, count)| format!("#{} ({})", tag, count));
+        let items = exams.chain(tags)
+            .zip(self.chosen.iter())
+            .map(|(name, chosen)| layout::get_checkbox_item(name, *chosen))
+            .collect();
+        self.list = Menu::new(items);
+        if selected.is_some() {
+            self.list.state.select(selected);
+        }
+    }
+
+    fn render_header<B: Backend>(&mut self, f: &mut Frame<B>, area: Rect) {
+        let text = vec![
+            Spans::from(Span::raw("")),
+            Spans::from(vec![
+                Span::styled(t!("name.short", locale = &self.locale), Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(" - "),
+                Span::raw(t!("title.builder", locale = &self.locale)),
+            ]),
+        ];
+        let header = layout::get_header(text);
+        let header_area = layout::get_default_column(area);
+
+        f.render_widget(header, header_area);
+    }
+
+    fn render_navbar<B: Backend>(&mut self, f: &mut Frame<B>, area: Rect) {
+        let navbar_e = navbar::get_elements(vec![NavType::Toggle, NavType::Order, NavType::Create, NavType::Back, NavType::Quit], self.locale.clone());
+        let navbar = layout::get_navbar(navbar_e);
+        let navbar_area = layout::get_default_column(area);
+
+        f.render_widget(navbar, navbar_area);
+    }
+
+    fn render_body<B: Backend>(&mut self, f: &mut Frame<B>, area: Rect) {
+        let area = layout::get_adaptative_column(area);
+        let rows = layout::get_header_body_layout(area, 3);
+
+        let order = match self.custom.order {
+            QuestionOrder::InOrder => t!("builder.order.in_order", locale = &self.locale),
+            QuestionOrder::Shuffled => t!("builder.order.shuffled", locale = &self.locale),
+        };
+        let settings = vec![
+            Spans::from(Span::raw(t!("builder.count", locale = &self.locale,
+                count = self.custom.count.min(self.available), available = self.available))),
+            Spans::from(Span::raw(t!("builder.order", locale = &self.locale, order = order))),
+        ];
+        f.render_widget(layout::get_par_default(settings), rows[0]);
+
+        let list = layout::create_navigable_list(self.list.items.clone());
+        f.render_stateful_widget(list, rows[1], &mut self.list.state);
+    }
+
+    fn render_empty_items<B: Backend>(&mut self, f: &mut Frame<B>, area: Rect) {
+        let text = vec![
+            Spans::from(Span::raw("")),
+            Spans::from(vec![
+                Span::styled(t!("test.empty.header", locale = &self.locale), Style::default().add_modifier(Modifier::BOLD)),
+            ]),
+            Spans::from(Span::raw("")),
+            Spans::from(Span::styled(t!("test.empty.info", locale = &self.locale), Style::default().add_modifier(Modifier::ITALIC))),
+            Spans::from(Span::raw("")),
+        ];
+        let empty = layout::get_par_default(text);
+        let empty_area = layout::get_default_column(area);
+
+        f.render_widget(empty, empty_area);
+    }
+}
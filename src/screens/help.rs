@@ -18,6 +18,8 @@ pub struct Help {
     pub locale: String,
     pub state: u8,
     pub import_results: Vec<String>,
+    // lines of the import log scrolled past, the problems found can make it long
+    import_scroll: u16,
     all_locales: Vec<(String, String)>,
     lang_name: String,
     langs: Menu,
@@ -48,6 +50,7 @@ impl Help {
             lang_name, 
             langs: langs_list,
             import_results: vec![],
+            import_scroll: 0,
         }
     }
 
@@ -78,22 +81,13 @@ impl Help {
             KeyCode::Char('c') | KeyCode::Char('C') => self.handle_lang_switch(),
             KeyCode::Char('i') | KeyCode::Char('I') => self.handle_import_switch(),
             KeyCode::Char('s') | KeyCode::Char('S') => return self.handle_import(),
+            KeyCode::Char('v') | KeyCode::Char('V') => return self.handle_validate(),
             KeyCode::Enter => return self.handle_enter(),
-            KeyCode::Up => {
-                if self.state == 1 {
-                    self.langs.previous();
-                }
-            },
-            KeyCode::Down => {
-                if self.state == 1 {
-                    self.langs.next();
-                }
-            },
-            KeyCode::Esc => {
-                if self.state == 1 || self.state == 2 || self.state == 4 {
-                    self.state = 0;
-                }
-            },
+            KeyCode::Up if self.state == 1 => self.langs.previous(),
+            KeyCode::Down if self.state == 1 => self.langs.next(),
+            KeyCode::Up if self.state == 4 => self.import_scroll = self.import_scroll.saturating_sub(1),
+            KeyCode::Down if self.state == 4 => self.import_scroll = (self.import_scroll + 1).min(self.import_results.len() as u16),
+            KeyCode::Esc if self.state == 1 || self.state == 2 || self.state == 4 => self.state = 0,
             _ => {}
         } 
         (ScreenType::Help, self.locale.clone())
@@ -104,8 +98,6 @@ impl Help {
             self.state = 1;
         } else if self.state == 1 {
             self.state = 0;
-        } else {
-            return;
         }
     }
 
@@ -114,8 +106,6 @@ impl Help {
             self.state = 2
         } else if self.state == 2 || self.state == 4 {
             self.state = 0;
-        } else {
-            return;
         }
     }
 
@@ -125,9 +115,21 @@ impl Help {
         }
 
         self.state = 3;
+        self.import_scroll = 0;
         (ScreenType::Importer, String::from(""))
     }
 
+    // checks the files like the import would, without saving anything
+    fn handle_validate(&mut self) -> (ScreenType, String) {
+        if self.state != 2 {
+            return (ScreenType::Help, String::from(""))
+        }
+
+        self.state = 3;
+        self.import_scroll = 0;
+        (ScreenType::Validator, String::from(""))
+    }
+
     fn handle_enter(&mut self) -> (ScreenType, String) {
         if self.state != 1 {
             return (ScreenType::Help, String::from(""));
@@ -205,11 +207,16 @@ impl Help {
                 Spans::from(Span::raw("---------")),
                 Spans::from(Span::raw("")),
             ];
+            // problems found in a file are indented under it
             self.import_results.iter()
-                .map(|r| Spans::from(Span::styled(r, Style::default().add_modifier(Modifier::ITALIC))))
+                .map(|r| match r.starts_with("  ") {
+                    true => Spans::from(Span::styled(r, Style::default().fg(Color::Yellow))),
+                    false => Spans::from(Span::styled(r, Style::default().add_modifier(Modifier::ITALIC))),
+                })
                 .for_each(|l| import_finish_text.push(l));
 
-            let import_finish = layout::get_par_with_colors(import_finish_text, Color::White, Color::Black);
+            let import_finish = layout::get_par_with_colors(import_finish_text, Color::White, Color::Black)
+                .scroll((self.import_scroll, 0));
 
             f.render_widget(import_finish, area);
         } else if self.state == 3 {
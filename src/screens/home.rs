@@ -16,11 +16,15 @@ pub struct Home {
     pub first_render: bool,
     locale: String,
     menu: Menu,
+    // questions due for review today
+    due: usize,
+    // review was chosen with nothing due, shown until the next key
+    review_empty: bool,
 }
 
 impl Home {
     pub fn new(locale: String) -> Self {
-        let mut home = Home { first_render: true, menu: Menu::new(vec![]), locale: String::from("") };
+        let mut home = Home { first_render: true, menu: Menu::new(vec![]), locale: String::from(""), due: 0, review_empty: false };
         home.update_locale(locale);
         home
     }
@@ -42,27 +46,47 @@ impl Home {
     }
 
     pub fn handle_key_code(&mut self, code: KeyCode) -> ScreenType {
+        self.review_empty = false;
         match code {
             KeyCode::Up => self.menu.previous(),
             KeyCode::Down => self.menu.next(),
             KeyCode::Enter => return self.handle_enter(),
             KeyCode::Char('t') | KeyCode::Char('T') => return ScreenType::Tests,
+            KeyCode::Char('c') | KeyCode::Char('C') => return ScreenType::Builder,
             KeyCode::Char('r') | KeyCode::Char('R') => return ScreenType::Rerun,
+            KeyCode::Char('v') | KeyCode::Char('V') => return ScreenType::Review,
+            KeyCode::Char('s') | KeyCode::Char('S') => return ScreenType::Stats,
             KeyCode::Char('h') | KeyCode::Char('H') => return ScreenType::Help,
             _ => {} 
         }
         ScreenType::Home
     }
 
+    pub fn show_review_empty(&mut self) {
+        self.review_empty = true;
+    }
+
+    pub fn set_due(&mut self, due: usize) {
+        self.due = due;
+        self.update_locale(self.locale.clone());
+    }
+
     pub fn update_locale(&mut self, locale: String) {
         self.locale = locale;
+        let selected = self.menu.state.selected();
         let menu_items = vec![
             format!("[t] {}", t!("menu.tests", locale = &self.locale)),
+            format!("[c] {}", t!("menu.builder", locale = &self.locale)),
             format!("[r] {}", t!("menu.redo", locale = &self.locale)),
+            format!("[v] {}", t!("menu.review", locale = &self.locale, count = self.due)),
+            format!("[s] {}", t!("menu.stats", locale = &self.locale)),
             format!("[h] {}", t!("menu.help", locale = &self.locale)),
             format!("[q] {}", t!("menu.exit", locale = &self.locale)),
         ];
         self.menu = Menu::new(menu_items);
+        if selected.is_some() {
+            self.menu.state.select(selected);
+        }
     }
 
     fn handle_enter(&mut self) -> ScreenType {
@@ -70,9 +94,12 @@ impl Home {
             Some(screen) => {
                 match screen {
                     0 => ScreenType::Tests,
-                    1 => ScreenType::Rerun,
-                    2 => ScreenType::Help,
-                    3 => ScreenType::Quit,
+                    1 => ScreenType::Builder,
+                    2 => ScreenType::Rerun,
+                    3 => ScreenType::Review,
+                    4 => ScreenType::Stats,
+                    5 => ScreenType::Help,
+                    6 => ScreenType::Quit,
                     _ => ScreenType::Home
                 }
             },
@@ -100,10 +127,17 @@ impl Home {
     }
 
     fn render_menu_instructions<B: Backend>(&mut self, f: &mut Frame<B>, area: Rect) {
-        let text = vec![
-            Spans::from(Span::raw(t!("home.instruction.l1", locale = &self.locale))),
-            Spans::from(Span::raw(t!("home.instruction.l2", locale = &self.locale))),
-        ];
+        let text = if self.review_empty {
+            vec![
+                Spans::from(Span::styled(t!("review.empty.header", locale = &self.locale), Style::default().add_modifier(Modifier::BOLD))),
+                Spans::from(Span::styled(t!("review.empty.info", locale = &self.locale), Style::default().add_modifier(Modifier::ITALIC))),
+            ]
+        } else {
+            vec![
+                Spans::from(Span::raw(t!("home.instruction.l1", locale = &self.locale))),
+                Spans::from(Span::raw(t!("home.instruction.l2", locale = &self.locale))),
+            ]
+        };
         let instructions = layout::get_par_with_borders(text);
         let instructions_area = layout::get_default_column(area);
     
@@ -1,5 +1,5 @@
 use crate::app::ScreenType;
-use crate::ui::{layout, menu::Menu, navbar, navbar::NavType};
+use crate::ui::{filter::TagFilter, layout, menu::Menu, navbar, navbar::NavType};
 
 use tui::{
     backend::Backend,
@@ -17,25 +17,75 @@ pub struct Rerun {
     pub locale: String,
     items: Vec<(String, String)>,
     list: Menu,
+    // test id and name when the attempt history of a test is displayed
+    history_for: Option<(String, String)>,
+    attempts: Vec<(String, String)>,
+    attempts_list: Menu,
+    filter: TagFilter,
 }
 
 impl Rerun {
     pub fn new(items: Vec<(String, String)>, locale: String) -> Self {
-        let names: Vec<String> = items.iter()
-            .map(|t| t.1.clone())
-            .collect();
-         Rerun{ first_render: true, locale: locale, items: items, list: Menu::new(names) }
+         let mut rerun = Rerun{ 
+             first_render: true,
+             locale,
+             items,
+             list: Menu::new(vec![]),
+             history_for: None,
+             attempts: vec![],
+             attempts_list: Menu::new(vec![]),
+             filter: TagFilter::new(vec![]),
+         };
+         rerun.update_list();
+         rerun
     }
 
     pub fn update_items(&mut self, items: Vec<(String, String)>) {
-        let names: Vec<String> = items.iter()
+        self.items = items;
+        self.update_list();
+        self.history_for = None;
+    }
+
+    pub fn set_tags(&mut self, tagged: Vec<(String, String)>) {
+        self.filter.set_tagged(tagged);
+        self.update_list();
+    }
+
+    fn visible(&self) -> Vec<&(String, String)> {
+        self.items.iter()
+            .filter(|t| self.filter.matches(&t.0))
+            .collect()
+    }
+
+    fn update_list(&mut self) {
+        let names: Vec<String> = self.visible().iter()
             .map(|t| t.1.clone())
             .collect();
-
-        self.items = items;
         self.list = Menu::new(names);
     }
 
+    pub fn show_history(&mut self, attempts: Vec<(String, String, bool, bool)>) {
+        let names: Vec<String> = attempts.iter()
+            .map(|t| {
+                let mut name = t.1.clone();
+                if t.2 {
+                    name = format!("{} {}", name, t!("history.practice", locale = &self.locale));
+                }
+                if t.3 {
+                    name = format!("{} {}", name, t!("history.retry", locale = &self.locale));
+                }
+                name
+            })
+            .collect();
+        self.history_for = self.list.state.selected().map(|idx| self.visible()[idx].clone());
+        self.attempts = attempts.into_iter().map(|t| (t.0, t.1)).collect();
+        self.attempts_list = Menu::new(names);
+    }
+
+    pub fn is_history(&self) -> bool {
+        self.history_for.is_some()
+    }
+
     pub fn draw<B: Backend>(&mut self, f: &mut Frame<B>) {
         if self.first_render {
             self.first_render = false;
@@ -50,7 +100,9 @@ impl Rerun {
 
         self.render_header(f, layout[0]);
         self.render_navbar(f, layout[1]);
-        if self.items.len() == 0 {
+        if self.is_history() {
+            self.render_history_items(f, layout[2]);
+        } else if self.items.is_empty() {
             self.render_empty_items(f, layout[2]);
         } else {
             self.render_test_items(f, layout[2]);
@@ -58,50 +110,99 @@ impl Rerun {
     }
 
     pub fn handle_key_code(&mut self, code: KeyCode) -> (ScreenType, String) {
+        if self.is_history() {
+            return self.handle_history_key_code(code);
+        }
         match code {
             KeyCode::Char('b') | KeyCode::Char('B') => return (ScreenType::Home, String::from("")),
             KeyCode::Char('r') | KeyCode::Char('R') => return self.handle_rerun(),
+            KeyCode::Char('h') | KeyCode::Char('H') => return self.handle_history(),
             KeyCode::Up => self.list.previous(),
             KeyCode::Down => self.list.next(),
             KeyCode::Enter => return self.handle_show_results(),
+            KeyCode::Char('g') | KeyCode::Char('G') => {
+                self.filter.next();
+                self.update_list();
+            },
             _ => {}
         } 
         (ScreenType::Rerun, String::from(""))
     }
 
+    fn handle_history_key_code(&mut self, code: KeyCode) -> (ScreenType, String) {
+        match code {
+            KeyCode::Char('b') | KeyCode::Char('B') => self.history_for = None,
+            KeyCode::Char('r') | KeyCode::Char('R') => return self.handle_rerun(),
+            KeyCode::Up => self.attempts_list.previous(),
+            KeyCode::Down => self.attempts_list.next(),
+            KeyCode::Enter => {
+                if let Some(idx) = self.attempts_list.state.selected() {
+                    return (ScreenType::Results, self.attempts[idx].0.to_string());
+                }
+            },
+            _ => {}
+        }
+        (ScreenType::Rerun, String::from(""))
+    }
+
+    fn handle_history(&mut self) -> (ScreenType, String) {
+        match self.list.state.selected() {
+            Some(idx) => (ScreenType::History, self.visible()[idx].0.to_string()),
+            None => (ScreenType::Rerun, String::from(""))
+        }
+    }
+
     fn handle_rerun(&mut self) -> (ScreenType, String) {
         match self.list.state.selected() {
-            Some(idx) => (ScreenType::Runner, self.items[idx].0.to_string()),
+            Some(idx) => (ScreenType::Runner, self.visible()[idx].0.to_string()),
             None => (ScreenType::Rerun, String::from(""))
         }
     }
 
     fn handle_show_results(&mut self) -> (ScreenType, String) {
         match self.list.state.selected() {
-            Some(idx) => (ScreenType::Results, self.items[idx].0.to_string()),
+            Some(idx) => (ScreenType::Results, self.visible()[idx].0.to_string()),
             None => (ScreenType::Rerun, String::from(""))
         }
     }
 
     fn render_header<B: Backend>(&mut self, f: &mut Frame<B>, area: Rect) {
-        let text = vec![
+        let mut text = vec![
             Spans::from(Span::raw("")),
             Spans::from(vec![
                 Span::styled(t!("name.short", locale = &self.locale), Style::default().add_modifier(Modifier::BOLD)),
                 Span::raw(" - "),
                 Span::raw(t!("title.rerun", locale = &self.locale)),
+                Span::raw(self.filter_label()),
             ]),
         ];
+        if let Some((_, name)) = &self.history_for {
+            text = vec![
+                Spans::from(Span::raw(t!("title.history", locale = &self.locale))),
+                Spans::from(Span::styled(name.clone(), Style::default().add_modifier(Modifier::BOLD))),
+            ];
+        }
         let header = layout::get_header(text);
         let header_area = layout::get_default_column(area);
     
         f.render_widget(header, header_area);
     }
 
+    fn filter_label(&self) -> String {
+        match &self.filter.current {
+            Some(tag) => format!(" {}", t!("tests.filter", locale = &self.locale, tag = tag)),
+            None => String::new(),
+        }
+    }
+
     fn render_navbar<B: Backend>(&mut self, f: &mut Frame<B>, area: Rect) {
-        let navbar_e = navbar::get_elements(vec![
-            NavType::Results, NavType::Rerun, NavType::Back, NavType::Quit
-        ], self.locale.clone());
+        let mut items = vec![NavType::Results, NavType::History, NavType::Rerun, NavType::Back, NavType::Quit];
+        if self.is_history() {
+            items = vec![NavType::Results, NavType::Rerun, NavType::Back, NavType::Quit];
+        } else if !self.filter.is_empty() {
+            items.insert(3, NavType::Filter);
+        }
+        let navbar_e = navbar::get_elements(items, self.locale.clone());
         let navbar = layout::get_navbar(navbar_e);
         let navbar_area = layout::get_default_column(area);
 
@@ -115,6 +216,13 @@ impl Rerun {
         f.render_stateful_widget(list, list_area, &mut self.list.state);
     }
 
+    fn render_history_items<B: Backend>(&mut self, f: &mut Frame<B>, area: Rect) {
+        let list = layout::create_navigable_list(self.attempts_list.items.clone());
+        let list_area = layout::get_adaptative_column(area);
+
+        f.render_stateful_widget(list, list_area, &mut self.attempts_list.state);
+    }
+
     fn render_empty_items<B: Backend>(&mut self, f: &mut Frame<B>, area: Rect) {
         let text = vec![
             Spans::from(Span::raw("")),
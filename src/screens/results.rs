@@ -1,11 +1,11 @@
 use crate::app::ScreenType;
-use crate::ui::{layout, navbar, navbar::NavType};
-use crate::models::test::{AnswerModel, ResultModel};
+use crate::ui::{layout, navbar, navbar::NavType, review};
+use crate::models::test::{AnswerModel, QuestionKind, ResultModel};
 
 use tui::{
     backend::Backend,
     layout::{Rect},
-    style::{Color, Modifier, Style},
+    style::{Modifier, Style},
     text::{Span, Spans},
     widgets::Clear,
     Frame,
@@ -59,6 +59,7 @@ impl Results {
     pub fn handle_key_code(&mut self, code: KeyCode) -> ScreenType  {
         match code {
             KeyCode::Char('b') | KeyCode::Char('B') => return ScreenType::Rerun,
+            KeyCode::Char('w') | KeyCode::Char('W') if self.has_wrong() => return ScreenType::Retry,
             KeyCode::Right => self.handle_next(),
             KeyCode::Left => self.handle_previous(),
             _ => {}
@@ -66,6 +67,14 @@ impl Results {
         ScreenType::Results
     }
 
+    pub fn result(&self) -> Option<ResultModel> {
+        self.item.clone()
+    }
+
+    fn has_wrong(&self) -> bool {
+        self.item.as_ref().is_some_and(|r| r.can_retry())
+    }
+
     fn handle_start(&mut self) {
         self.current_q_idx = 0;
         self.current_q = Some(self.item.clone().unwrap().answers[0].clone());
@@ -107,41 +116,34 @@ impl Results {
 
         let layout = layout::get_header_body_layout(area, 3);
 
-        let navbar_b = navbar::get_elements(
-            vec![NavType::Next, NavType::Previous, NavType::Back, NavType::Quit],
-            self.locale.clone()
-        );
+        let mut items = vec![NavType::Next, NavType::Previous, NavType::Back, NavType::Quit];
+        if self.has_wrong() {
+            items.insert(2, NavType::Retry);
+        }
+        let navbar_b = navbar::get_elements(items, self.locale.clone());
         let navbar = layout::get_navbar(navbar_b);
         let navbar_area = layout::get_default_column(layout[0]);
         f.render_widget(navbar, navbar_area);
 
         let content_area = layout::get_column_with_margin(layout[1], 20, 150);
         if self.current_q_idx == self.count_q {
-            let summary = layout::render_summary_table(self.item.clone().unwrap().answers, &self.locale);
-            f.render_widget(summary, content_area);
+            let item = self.item.clone().unwrap();
+            let layout = layout::get_header_body_layout(content_area, 2);
+            if let Some(grade) = item.grade.as_ref() {
+                f.render_widget(layout::get_par_default(review::get_grade_spans(grade, &self.locale)), layout[0]);
+            }
+            let summary = layout::render_summary_table(item.answers, &self.locale);
+            f.render_widget(summary, layout[1]);
         } else {
-            let mut aidx: usize = 0;
-            let answers_spans = q.answers.iter()
-                .map(|a| {
-                    let mut color = Color::Black;
-                    if aidx == q.given.unwrap() {
-                        color = Color::Red;
-                    }
-                    //if aidx == q.correct.into() {
-                    if aidx == usize::from(q.correct) {
-                        color = Color::Green;
-                    }
-                    aidx += 1;
-                    Spans::from(Span::styled(
-                            a.as_str(), 
-                            Style::default().bg(color).fg(Color::White)))
-                })
-                .collect::<Vec<Spans>>();
+            let answers_spans = review::get_answer_spans(&q, &self.locale);
+            let question = match q.kind {
+                QuestionKind::Cloze => q.filled_question(),
+                _ => q.question,
+            };
 
             let answers_page = layout::get_results_q_page(self.current_q_idx + 1, 
-                self.count_q, q.question, answers_spans, q.time, &self.locale);
+                self.count_q, question, answers_spans, q.time, &self.locale);
             f.render_widget(answers_page, content_area);
         }
     }
 }
-
@@ -1,8 +1,13 @@
 use crate::app::ScreenType;
-use crate::ui::{layout, menu::Menu, navbar, navbar::NavType};
-use crate::models::test::{TestModel, ResultModel, AnswerModel};
+use crate::ui::{layout, layout::Clock, menu::Menu, navbar, navbar::NavType, review};
+use crate::models::test::{self, TestModel, ResultModel, AnswerModel, GapAnswer, GapModel, ProgressModel, QuestionKind, QuestionModel};
+use crate::models::matching::{MatchKind, MatchOptions};
+use crate::models::scoring::{GradeScale, Scoring};
 
-use std::time::{Instant};
+use std::collections::HashMap;
+use std::mem;
+use std::time::{Duration, Instant};
+use rand::seq::SliceRandom;
 
 use tui::{
     backend::Backend,
@@ -19,13 +24,58 @@ pub struct Runner {
     pub first_render: bool,
     pub locale: String,
     pub origin: ScreenType,
+    pub match_options: MatchOptions,
+    // global shuffle option, the test can override it
+    pub shuffle: bool,
+    // global time limits in seconds, 0 means no limit
+    pub question_limit: u64,
+    pub time_limit: u64,
+    // global grade scale code, the test can override it
+    pub grade_scale: String,
+    // attempt whose wrong answers are asked again
+    pub retry_of: Option<String>,
     item: Option<TestModel>,
+    // a paused attempt of the test, starting the test continues it
+    progress: Option<ProgressModel>,
+    // order in which the questions are asked, indices into the test questions
+    question_order: Vec<usize>,
+    // saved answer, flag and seconds spent over all visits for every question in the asked order
+    answers: Vec<Option<AnswerModel>>,
+    flagged: Vec<bool>,
+    times: Vec<u64>,
+    // shuffled order of the answers of every question and gap, kept when coming back to a question
+    answer_orders: HashMap<(usize, usize), Vec<usize>>,
     current_q_number: usize,
     current_q_text: String,
     current_q_answers: Menu,
+    // answer index in the test for every item shown in the answers menu
+    current_q_order: Vec<usize>,
+    // answers toggled with space in 'select all that apply' questions
+    current_q_checked: Vec<usize>,
+    // text entered so far in typed questions
+    current_q_typed: String,
+    // the answer was changed since the question was shown, it is kept when moving away
+    current_q_touched: bool,
+    // gaps of a cloze question are answered one after another, the one shown was changed since it was shown
+    current_q_gap: usize,
+    current_q_gap_touched: bool,
+    current_q_gaps: Vec<GapAnswer>,
+    // grid of all questions, shown after the last question and before submitting
+    show_overview: bool,
+    overview_cursor: usize,
+    // practice mode checks every answer right away and waits for a key before moving on
+    practice: bool,
+    show_feedback: bool,
     result: ResultModel,
+    // questions asked in this attempt, drawn from all the questions of the test
     question_count: usize,
+    pool_size: usize,
+    sample_size: usize,
     show_summary: bool,
+    // the test was just submitted and its result is not saved yet
+    submitted: bool,
+    // retries and reviews cannot be resumed, leaving them takes the same key twice
+    confirm_leave: Option<ScreenType>,
     timer_t: Instant,
     timer_q: Instant,
 }
@@ -33,31 +83,90 @@ pub struct Runner {
 impl Runner {
     pub fn new(item: Option<TestModel>, locale: String) -> Self {
          let mut question_count = 0; 
-         if !item.is_none() {
-             question_count = item.clone().unwrap().questions.len();
+         let mut sample_size = 0;
+         if let Some(ref t) = item {
+             question_count = t.questions.len();
+             sample_size = t.sample_size.unwrap_or(question_count).min(question_count);
          }
          Runner {
              first_render: true,
-             locale: locale,
+             locale,
              item, 
              current_q_number: 0,
              current_q_text: String::from(""),
              current_q_answers: Menu::new(vec![]),
+             current_q_order: vec![],
+             current_q_checked: vec![],
+             current_q_typed: String::new(),
+             current_q_touched: false,
+             current_q_gap: 0,
+             current_q_gap_touched: false,
+             current_q_gaps: vec![],
+             show_overview: false,
+             overview_cursor: 0,
+             practice: false,
+             show_feedback: false,
              result: ResultModel::new(String::from(""), String::from(""), vec![], 0),
              question_count, 
+             pool_size: question_count,
+             sample_size,
              show_summary: false,
+             submitted: false,
+             confirm_leave: None,
              timer_t: Instant::now(),
              timer_q: Instant::now(),
              origin: ScreenType::Tests,
+             match_options: MatchOptions::default(),
+             shuffle: false,
+             question_limit: 0,
+             time_limit: 0,
+             question_order: (0..question_count).collect(),
+             answers: vec![None; question_count],
+             flagged: vec![false; question_count],
+             times: vec![0; question_count],
+             answer_orders: HashMap::new(),
+             progress: None,
+             retry_of: None,
+             grade_scale: String::new(),
          }
     }
 
     pub fn is_running(&self) -> bool {
-        if self.current_q_number == 0 {
-            false
-        } else {
-            true
+        self.current_q_number != 0
+    }
+
+    pub fn set_progress(&mut self, progress: Option<ProgressModel>) {
+        // the test could have changed since it was paused
+        self.progress = progress.filter(|p| !p.question_order.is_empty() && p.question_order.iter().all(|idx| *idx < self.pool_size));
+    }
+
+    // stops the test and returns everything needed to continue it later
+    pub fn pause(&mut self) -> Option<ProgressModel> {
+        if !self.is_running() {
+            return None;
+        }
+        if !self.show_overview {
+            self.leave_question();
         }
+        let answers = self.answers.iter()
+            .enumerate()
+            .filter_map(|(pos, a)| a.clone().map(|mut a| {
+                a.time = self.times[pos];
+                a
+            }))
+            .collect::<Vec<AnswerModel>>();
+        // the test continues with the question shown now
+        let current = self.current_q_number - 1;
+        let mut result = ResultModel::new(self.result.id.clone(), self.result.title.clone(), answers, self.timer_t.elapsed().as_secs());
+        result.practice = self.practice;
+        result.retry_of = self.retry_of.clone();
+        let mut progress = ProgressModel::new(result, self.question_order.clone(), self.times[current]);
+        progress.flagged = (0..self.question_count).filter(|pos| self.flagged[*pos]).collect();
+        progress.current = Some(current);
+        self.current_q_number = 0;
+        self.show_overview = false;
+        self.show_feedback = false;
+        Some(progress)
     }
 
     pub fn draw<B: Backend>(&mut self, f: &mut Frame<B>) {
@@ -71,7 +180,11 @@ impl Runner {
         f.render_widget(background, f.size());
 
         if self.is_running() {
-            self.render_question_page(f);
+            if self.show_overview {
+                self.render_overview_page(f);
+            } else {
+                self.render_question_page(f);
+            }
         } else {
             if self.show_summary {
                 let layout = layout::get_header_navbar_layout(f.size(), 3, 3);
@@ -89,7 +202,33 @@ impl Runner {
     }
 
     pub fn handle_key_code(&mut self, code: KeyCode) -> (ScreenType, Option<ResultModel>) {
+        let asked = self.confirm_leave.take();
+        let (screen, result) = self.handle_running_key(code);
+        let leaving = screen == ScreenType::Pause || screen == ScreenType::Quit;
+        if leaving && self.is_running() && !self.can_resume() && asked.as_ref() != Some(&screen) {
+            self.confirm_leave = Some(screen);
+            return (ScreenType::Runner, None);
+        }
+        (screen, result)
+    }
+
+    // the answers of a paused test are kept, the ones of a retry or a review are not
+    fn can_resume(&self) -> bool {
+        self.retry_of.is_none() && !self.result.is_review()
+    }
+
+    fn handle_running_key(&mut self, code: KeyCode) -> (ScreenType, Option<ResultModel>) {
+        if self.is_running() && self.show_overview {
+            return self.handle_overview(code);
+        }
+        if self.is_running() && self.show_feedback {
+            return self.handle_feedback(code);
+        }
+        if self.is_typing() {
+            return self.handle_typing(code);
+        }
         match code {
+            KeyCode::Esc if self.is_running() => return (ScreenType::Pause, None),
             KeyCode::Char('b') | KeyCode::Char('B') => {
                 if self.is_running() {
                     return (ScreenType::Runner, None)
@@ -102,107 +241,569 @@ impl Runner {
             }
             // a safeguard
             // should have a confirmation dialog
-            KeyCode::Char('P') => {
-                if self.is_running() {
-                    return (ScreenType::Quit, None);
-                }
+            KeyCode::Char('P') if self.is_running() => return (ScreenType::Quit, None),
+            KeyCode::Char('p') if self.is_running() => return (ScreenType::Pause, None),
+            KeyCode::Char('s') | KeyCode::Char('S') if !self.is_running() && !self.show_summary => {
+                return self.start_test(false)
             },
-            KeyCode::Char('s') | KeyCode::Char('S') => {
-                if !self.is_running() && !self.show_summary {
-                    return self.start_test()
-                }
+            KeyCode::Char('p') if !self.is_running() && !self.show_summary => {
+                return self.start_test(true)
             },
-            KeyCode::Up => {
-                if self.is_running() {
-                    self.current_q_answers.previous()
-                }
+            KeyCode::Char('+') if !self.is_running() && !self.show_summary => self.change_sample(1),
+            KeyCode::Char('-') if !self.is_running() && !self.show_summary => self.change_sample(-1),
+            KeyCode::Up if self.is_running() => {
+                self.current_q_answers.previous();
+                self.current_q_touched = true;
+                self.current_q_gap_touched = true;
             },
-            KeyCode::Down => {
-                if self.is_running() {
-                    self.current_q_answers.next()
-                }
+            KeyCode::Down if self.is_running() => {
+                self.current_q_answers.next();
+                self.current_q_touched = true;
+                self.current_q_gap_touched = true;
             },
-            KeyCode::Char('d') | KeyCode::Char('D') => {
-                if self.show_summary {
-                    self.show_summary = false;
-                    return (ScreenType::Results, Some(self.result.clone()));
-                }
+            KeyCode::Char(' ') if self.is_running() => self.handle_toggle(),
+            KeyCode::Char('f') | KeyCode::Char('F') if self.is_running() => self.toggle_flag(self.current_q_number - 1),
+            KeyCode::Char('d') | KeyCode::Char('D') if self.show_summary => {
+                self.show_summary = false;
+                return (ScreenType::Results, Some(self.result.clone()));
+            },
+            KeyCode::Char('w') | KeyCode::Char('W') if self.show_summary && self.result.can_retry() => {
+                self.show_summary = false;
+                return (ScreenType::Retry, Some(self.result.clone()));
             },
             KeyCode::Enter => return self.handle_enter(),
+            other if self.is_running() => { self.handle_navigation(other); },
             _ => {}
         } 
         (ScreenType::Runner, None)
     }
 
-    fn start_test(&mut self) -> (ScreenType, Option<ResultModel>) {
+    fn start_test(&mut self, practice: bool) -> (ScreenType, Option<ResultModel>) {
         // TODO check when test has 0 questions
         // how to handle that so the user can see?
         // not allow to have that test shown on list?
 
         let test_m = self.item.clone().unwrap();
-
-        self.timer_t = Instant::now();
-        self.timer_q = Instant::now();
-        self.current_q_number = 1;
-        self.show_summary = false;
+        // a paused attempt keeps the questions drawn when it was started
+        let order = match self.progress.as_ref() {
+            Some(p) => p.question_order.clone(),
+            None => test_m.draw_sample(self.sample_size, &mut rand::thread_rng()),
+        };
         self.result = ResultModel::new(
-            String::from(test_m.id),
-            String::from(test_m.title),
+            test_m.id,
+            test_m.title,
             vec![], 
             0
             );
+        self.practice = practice;
+        self.result.practice = practice;
+        self.result.retry_of = self.retry_of.clone();
+        self.question_count = order.len();
+        self.answers = vec![None; self.question_count];
+        self.flagged = vec![false; self.question_count];
+        self.times = vec![0; self.question_count];
+        self.answer_orders = HashMap::new();
+        self.show_summary = false;
+        self.show_overview = false;
+        self.show_feedback = false;
+        if let Some(progress) = self.progress.take() {
+            self.resume(progress);
+            return (ScreenType::Runner, None);
+        }
 
-        // should here be a None check?
-        let tmp_test = self.item.clone().unwrap();
-        self.current_q_text = tmp_test.questions[0].question.clone();
-        let answers_list: Vec<String> = tmp_test.questions[0].answers.clone().iter()
-            .map(|i| i.clone()).collect();
-        self.current_q_answers = Menu::new(answers_list);
+        self.timer_t = Instant::now();
+        self.timer_q = Instant::now();
+        self.current_q_number = 1;
+        self.question_order = order;
+        if self.is_shuffled() {
+            self.question_order.shuffle(&mut rand::thread_rng());
+        }
+
+        self.load_question();
 
         (ScreenType::Runner, None)
     }
 
+    fn change_sample(&mut self, by: isize) {
+        if self.progress.is_none() {
+            self.sample_size = self.sample_size.saturating_add_signed(by).clamp(1.min(self.pool_size), self.pool_size);
+        }
+    }
+
+    fn resume(&mut self, progress: ProgressModel) {
+        // a paused attempt continues in the mode it was started in
+        self.practice = progress.result.practice;
+        self.result.practice = progress.result.practice;
+        let order = progress.question_order;
+        // answers come back in test order, they are put back where their question was asked
+        let questions = self.item.as_ref().map(|i| i.questions.clone()).unwrap_or_default();
+        for answer in progress.result.answers {
+            if let Some(pos) = order.iter().position(|idx| questions.get(*idx).is_some_and(|q| q.id == answer.question_id)) {
+                self.times[pos] = answer.time;
+                self.answers[pos] = Some(answer);
+            }
+        }
+        self.question_order = order;
+        for pos in progress.flagged {
+            if let Some(flag) = self.flagged.get_mut(pos) {
+                *flag = true;
+            }
+        }
+        self.timer_t = Instant::now().checked_sub(Duration::from_secs(progress.result.total_time)).unwrap_or_else(Instant::now);
+        self.timer_q = Instant::now();
+
+        let current = progress.current.filter(|pos| *pos < self.question_count);
+        match current.or_else(|| self.first_unanswered()) {
+            Some(pos) => {
+                self.times[pos] = progress.question_time;
+                self.current_q_number = pos + 1;
+                self.load_question();
+            },
+            None => {
+                self.current_q_number = self.question_count;
+                self.load_question();
+                self.open_overview();
+            },
+        }
+    }
+
+    fn first_unanswered(&self) -> Option<usize> {
+        self.answers.iter().position(|a| a.is_none())
+    }
+
+    // while a typed question or gap is shown every character goes to the answer
+    fn is_typing(&self) -> bool {
+        if !self.is_running() {
+            return false;
+        }
+        match self.current_question().kind {
+            QuestionKind::Typed => true,
+            QuestionKind::Cloze => self.current_gap().is_some_and(|g| g.typed),
+            _ => false,
+        }
+    }
+
+    fn handle_typing(&mut self, code: KeyCode) -> (ScreenType, Option<ResultModel>) {
+        match code {
+            KeyCode::Esc => return (ScreenType::Pause, None),
+            KeyCode::Char(c) => {
+                self.current_q_typed.push(c);
+                self.current_q_touched = true;
+                self.current_q_gap_touched = true;
+            },
+            KeyCode::Backspace => {
+                self.current_q_typed.pop();
+                self.current_q_touched = true;
+                self.current_q_gap_touched = true;
+            },
+            KeyCode::Enter => return self.handle_enter(),
+            other => { self.handle_navigation(other); },
+        }
+        (ScreenType::Runner, None)
+    }
+
+    // keys that work the same way in every kind of question
+    fn handle_navigation(&mut self, code: KeyCode) {
+        match code {
+            KeyCode::Left if self.can_go_back() && self.current_q_number > 1 => self.go_to(self.current_q_number - 1),
+            KeyCode::Right => self.advance(),
+            KeyCode::Tab if self.can_go_back() => self.open_overview(),
+            KeyCode::F(2) => self.toggle_flag(self.current_q_number - 1),
+            _ => {}
+        }
+    }
+
+    fn handle_overview(&mut self, code: KeyCode) -> (ScreenType, Option<ResultModel>) {
+        let last = self.question_count.saturating_sub(1);
+        match code {
+            KeyCode::Esc | KeyCode::Char('p') => return (ScreenType::Pause, None),
+            KeyCode::Char('P') => return (ScreenType::Quit, None),
+            KeyCode::Left => self.overview_cursor = self.overview_cursor.saturating_sub(1),
+            KeyCode::Right => self.overview_cursor = (self.overview_cursor + 1).min(last),
+            KeyCode::Up => self.overview_cursor = self.overview_cursor.saturating_sub(10),
+            KeyCode::Down => self.overview_cursor = (self.overview_cursor + 10).min(last),
+            KeyCode::Char('f') | KeyCode::Char('F') | KeyCode::F(2) => self.toggle_flag(self.overview_cursor),
+            KeyCode::Enter if self.can_go_back() => self.close_overview(self.overview_cursor + 1),
+            KeyCode::Tab | KeyCode::Char('b') | KeyCode::Char('B') if self.can_go_back() => self.close_overview(self.current_q_number),
+            KeyCode::Char('s') | KeyCode::Char('S') => self.submit(false),
+            _ => {}
+        }
+        (ScreenType::Runner, None)
+    }
+
+    fn handle_feedback(&mut self, code: KeyCode) -> (ScreenType, Option<ResultModel>) {
+        self.show_feedback = false;
+        match code {
+            KeyCode::Esc => return (ScreenType::Pause, None),
+            _ => self.advance(),
+        }
+        (ScreenType::Runner, None)
+    }
+
+    // with a time limit per question there is no coming back to a question
+    fn can_go_back(&self) -> bool {
+        self.get_question_limit().is_none()
+    }
+
+    fn question_at(&self, pos: usize) -> QuestionModel {
+        self.item.as_ref().unwrap().questions[self.question_order[pos]].clone()
+    }
+
+    fn current_question(&self) -> QuestionModel {
+        self.question_at(self.current_q_number - 1)
+    }
+
+    fn get_question_limit(&self) -> Option<u64> {
+        self.item.as_ref().and_then(|i| i.question_limit).or(Some(self.question_limit)).filter(|l| *l > 0)
+    }
+
+    fn get_time_limit(&self) -> Option<u64> {
+        self.item.as_ref().and_then(|i| i.time_limit).or(Some(self.time_limit)).filter(|l| *l > 0)
+    }
+
+    // saves the current answer when the question time is up,
+    // when the test time is up the test is submitted as it is
+    pub fn on_tick(&mut self) {
+        if !self.is_running() {
+            return;
+        }
+        if self.get_time_limit().is_some_and(|l| self.timer_t.elapsed().as_secs() >= l) {
+            if !self.show_overview {
+                self.time_out();
+            }
+            self.submit(true);
+        } else if !self.show_overview && !self.show_feedback && self.get_question_limit().is_some_and(|l| self.timer_q.elapsed().as_secs() >= l) {
+            self.time_out();
+            self.advance();
+        }
+    }
+
+    fn time_out(&mut self) {
+        let pos = self.current_q_number - 1;
+        // an answer saved before is kept unless it was changed
+        if self.answers[pos].is_some() && !self.current_q_touched {
+            return;
+        }
+        let q = self.current_question();
+        // the first answer is highlighted from the start, it only counts once the user moved to it
+        if !self.current_q_touched {
+            let time = self.times[pos] + self.timer_q.elapsed().as_secs();
+            let mut answer = match q.kind {
+                QuestionKind::Cloze => AnswerModel::cloze(&q, vec![], time),
+                _ => AnswerModel::new(&q, vec![], time),
+            };
+            answer.timed_out = true;
+            self.answers[pos] = Some(answer);
+            return;
+        }
+        // gaps entered so far are kept, the one shown only when it was changed, the rest stay empty
+        if q.kind == QuestionKind::Cloze {
+            if self.current_q_gap_touched {
+                self.next_gap();
+            }
+            for gap in q.gaps.iter().skip(self.current_q_gaps.len()) {
+                self.current_q_gaps.push(GapAnswer::with_match(gap, None, String::new(), MatchKind::Wrong));
+            }
+        }
+        self.record_answer(true);
+    }
+
+    fn get_scoring(&self) -> Scoring {
+        let item = self.item.as_ref();
+        let scale = item.and_then(|i| i.grade_scale.clone()).unwrap_or(self.grade_scale.clone());
+        Scoring {
+            negative: item.and_then(|i| i.negative).unwrap_or(0.0),
+            pass_threshold: item.and_then(|i| i.pass_threshold),
+            scale: GradeScale::from_code(&scale),
+        }
+    }
+
+    fn is_shuffled(&self) -> bool {
+        self.item.as_ref().and_then(|i| i.shuffle).unwrap_or(self.shuffle)
+    }
+
+    // gap 0 holds the answers of the question itself
+    fn load_answers(&mut self, gap: usize, answers: Vec<String>) {
+        let key = (self.current_q_number - 1, gap);
+        let order = match self.answer_orders.get(&key) {
+            Some(order) => order.clone(),
+            None => {
+                let mut order = (0..answers.len()).collect::<Vec<usize>>();
+                if self.is_shuffled() {
+                    order.shuffle(&mut rand::thread_rng());
+                }
+                self.answer_orders.insert(key, order.clone());
+                order
+            },
+        };
+        self.current_q_answers = Menu::new(order.iter().map(|idx| answers[*idx].clone()).collect());
+        self.current_q_order = order;
+    }
+
+    // maps answers picked in the menu back to their index in the test
+    fn canonical(&self, shown: &[usize]) -> Vec<usize> {
+        let mut given = shown.iter()
+            .filter_map(|idx| self.current_q_order.get(*idx).copied())
+            .collect::<Vec<usize>>();
+        given.sort();
+        given
+    }
+
+    fn shown(&self, canonical: usize) -> Option<usize> {
+        self.current_q_order.iter().position(|idx| *idx == canonical)
+    }
+
+    fn selected(&self) -> Option<usize> {
+        self.current_q_answers.state.selected()
+            .and_then(|idx| self.current_q_order.get(idx).copied())
+    }
+
+    fn current_gap(&self) -> Option<GapModel> {
+        self.current_question().gaps.get(self.current_q_gap).cloned()
+    }
+
+    fn load_question(&mut self) {
+        let q = self.current_question();
+        self.current_q_text = q.question.clone();
+        self.load_answers(0, q.answers.clone());
+        self.current_q_checked = vec![];
+        self.current_q_typed = String::new();
+        self.current_q_touched = false;
+        self.current_q_gap = 0;
+        self.current_q_gap_touched = false;
+        self.current_q_gaps = vec![];
+        if let Some(gap) = self.current_gap() {
+            self.load_answers(1, gap.answers);
+        }
+
+        // coming back to a question shows the answer saved before
+        let Some(saved) = self.answers[self.current_q_number - 1].clone() else {
+            return;
+        };
+        match q.kind {
+            QuestionKind::Single => {
+                let shown = saved.given.first().and_then(|g| self.shown(*g));
+                if shown.is_some() {
+                    self.current_q_answers.state.select(shown);
+                }
+            },
+            QuestionKind::Multiple => {
+                self.current_q_checked = saved.given.iter().filter_map(|g| self.shown(*g)).collect();
+            },
+            QuestionKind::Typed => self.current_q_typed = saved.typed.clone(),
+            QuestionKind::Cloze => {},
+        }
+    }
+
+    // keeps a changed answer and the time spent on the question before showing another one
+    fn leave_question(&mut self) {
+        if self.current_q_touched && self.current_question().kind != QuestionKind::Cloze {
+            self.record_answer(false);
+        }
+        self.times[self.current_q_number - 1] += self.timer_q.elapsed().as_secs();
+        self.timer_q = Instant::now();
+    }
+
+    fn go_to(&mut self, number: usize) {
+        self.leave_question();
+        self.current_q_number = number;
+        self.load_question();
+    }
+
+    fn advance(&mut self) {
+        if self.current_q_number < self.question_count {
+            self.go_to(self.current_q_number + 1);
+        } else {
+            self.open_overview();
+        }
+    }
+
+    fn open_overview(&mut self) {
+        self.leave_question();
+        self.overview_cursor = self.current_q_number - 1;
+        self.show_overview = true;
+    }
+
+    fn close_overview(&mut self, number: usize) {
+        self.show_overview = false;
+        self.current_q_number = number;
+        self.timer_q = Instant::now();
+        self.load_question();
+    }
+
+    fn toggle_flag(&mut self, pos: usize) {
+        if let Some(flag) = self.flagged.get_mut(pos) {
+            *flag = !*flag;
+        }
+    }
+
+    // stores the current gap and moves to the next one, returns false after the last gap
+    fn next_gap(&mut self) -> bool {
+        let Some(gap) = self.current_gap() else {
+            return false;
+        };
+        let answer = if gap.typed {
+            GapAnswer::typed(&gap, self.current_q_typed.trim().to_string(), &self.match_options)
+        } else {
+            GapAnswer::choice(&gap, self.selected())
+        };
+        self.current_q_gaps.push(answer);
+        self.current_q_gap += 1;
+        self.current_q_gap_touched = false;
+        self.current_q_typed = String::new();
+        self.current_q_touched = true;
+        match self.current_gap() {
+            Some(next) => {
+                self.load_answers(self.current_q_gap + 1, next.answers);
+                true
+            },
+            None => false,
+        }
+    }
+
+    // the question text with the gaps answered so far filled in
+    fn cloze_text(&self) -> String {
+        let q = self.current_question();
+        let values = (0..q.gaps.len())
+            .map(|idx| match self.current_q_gaps.get(idx) {
+                Some(answer) => Some(answer.text()),
+                None if idx == self.current_q_gap && !self.current_q_typed.is_empty() => Some(self.current_q_typed.clone()),
+                None => None,
+            })
+            .collect::<Vec<Option<String>>>();
+        test::fill_gaps(&q.question, &values)
+    }
+
+    fn handle_toggle(&mut self) {
+        if self.current_question().kind != QuestionKind::Multiple {
+            return;
+        }
+        if let Some(idx) = self.current_q_answers.state.selected() {
+            match self.current_q_checked.iter().position(|c| *c == idx) {
+                Some(pos) => { self.current_q_checked.remove(pos); },
+                None => self.current_q_checked.push(idx),
+            }
+            self.current_q_touched = true;
+        }
+    }
+
     fn handle_enter(&mut self) -> (ScreenType, Option<ResultModel>) {
         if self.is_running() {
-            let q = self.item.clone().unwrap().questions[self.current_q_number - 1].clone();
-            let answers = q.answers.iter()
-                .map(|a| a.clone())
-                .collect::<Vec<String>>();
-            let answer = AnswerModel::new(
-                    self.current_q_text.clone(),
-                    answers,
-                    q.correct,
-                    self.current_q_answers.state.selected(),
-                    q.is_correct(self.current_q_answers.state.selected()),
-                    self.timer_q.elapsed().as_secs() 
-                ); 
-            self.result.answers.push(answer);
-
-            if self.current_q_number == self.question_count {
-                self.current_q_number = 0;
-                self.show_summary = true;
-                self.result.total_time = self.timer_t.elapsed().as_secs();
-
+            if self.current_question().kind == QuestionKind::Cloze && self.next_gap() {
+                return (ScreenType::Runner, None);
+            }
+            self.record_answer(false);
+            if self.practice {
+                self.show_feedback = true;
             } else {
-                let tmp_test = self.item.clone().unwrap();
-                self.current_q_text = tmp_test.questions[self.current_q_number].question.clone();
-                let answers_list: Vec<String> = tmp_test.questions[self.current_q_number].answers.clone().iter()
-                    .map(|i| i.clone()).collect();
-                self.current_q_answers = Menu::new(answers_list);
-                self.current_q_number += 1;
-                self.timer_q = Instant::now();
+                self.advance();
             }
         }
         (ScreenType::Runner, None)
     }
 
+    fn record_answer(&mut self, timed_out: bool) {
+        let q = self.current_question();
+        let pos = self.current_q_number - 1;
+        let time = self.times[pos] + self.timer_q.elapsed().as_secs();
+        let mut answer = match q.kind {
+            QuestionKind::Single => AnswerModel::new(&q, self.selected().into_iter().collect(), time),
+            QuestionKind::Multiple => AnswerModel::new(&q, self.canonical(&self.current_q_checked), time),
+            QuestionKind::Typed => AnswerModel::typed(&q, self.current_q_typed.trim().to_string(), &self.match_options, time),
+            QuestionKind::Cloze => AnswerModel::cloze(&q, self.current_q_gaps.clone(), time),
+        };
+        answer.timed_out = timed_out;
+        self.answers[pos] = Some(answer);
+        self.current_q_touched = false;
+    }
+
+    // the result is only built now, questions without an answer count as unanswered
+    fn submit(&mut self, timed_out: bool) {
+        self.result.answers = (0..self.question_count)
+            .map(|pos| {
+                let mut answer = self.answers[pos].clone().unwrap_or_else(|| {
+                    let q = self.question_at(pos);
+                    let mut answer = match q.kind {
+                        QuestionKind::Cloze => AnswerModel::cloze(&q, vec![], 0),
+                        _ => AnswerModel::new(&q, vec![], 0),
+                    };
+                    answer.timed_out = timed_out;
+                    answer
+                });
+                answer.time = self.times[pos];
+                answer
+            })
+            .collect();
+        self.result.total_time = self.timer_t.elapsed().as_secs();
+        self.result.grade = Some(self.get_scoring().grade(&self.result.answers));
+        self.current_q_number = 0;
+        self.show_overview = false;
+        self.show_feedback = false;
+        self.show_summary = true;
+        self.submitted = true;
+    }
+
+    // the result of a test submitted since the last call, to be saved right away
+    pub fn take_submitted(&mut self) -> Option<ResultModel> {
+        if !mem::take(&mut self.submitted) {
+            return None;
+        }
+        Some(self.result.clone())
+    }
+
+    // the summary leads to the saved attempt
+    pub fn set_attempt(&mut self, attempt: Option<String>) {
+        self.result.attempt = attempt;
+    }
+
     fn render_question_page<B: Backend>(&mut self, f: &mut Frame<B>) {
-        let layout = layout::get_two_row_layout(f.size(), 40);
+        let layout = layout::get_header_body_layout(f.size(), 3);
+        let mut items = vec![NavType::Previous, NavType::Next, NavType::Flag, NavType::Overview, NavType::Pause];
+        if !self.can_go_back() {
+            items = vec![NavType::Next, NavType::Flag, NavType::Pause];
+        }
+        if self.show_feedback {
+            items = vec![NavType::Next, NavType::Pause];
+        }
+        self.render_navbar(f, layout[0], items);
+
+        let layout = layout::get_two_row_layout(layout[1], 40);
 
         self.render_question(f, layout[0]);
         self.render_answers(f, layout[1]);
     }
 
+    fn render_overview_page<B: Backend>(&mut self, f: &mut Frame<B>) {
+        let layout = layout::get_header_body_layout(f.size(), 3);
+        let mut items = vec![NavType::Open, NavType::Flag, NavType::Submit, NavType::Back, NavType::Pause];
+        if !self.can_go_back() {
+            items = vec![NavType::Flag, NavType::Submit, NavType::Pause];
+        }
+        self.render_navbar(f, layout[0], items);
+
+        let cells = self.answers.iter()
+            .zip(self.flagged.iter())
+            .map(|(a, flagged)| (a.is_some(), *flagged))
+            .collect::<Vec<(bool, bool)>>();
+        let grid = layout::get_overview_grid(cells, self.overview_cursor, &self.locale);
+        let grid_area = layout::get_column_with_margin(layout[1], 30, 150);
+
+        f.render_widget(grid, grid_area);
+    }
+
+    fn render_navbar<B: Backend>(&mut self, f: &mut Frame<B>, area: Rect, items: Vec<NavType>) {
+        if self.confirm_leave.is_some() {
+            let text = vec![Spans::from(Span::styled(t!("runner.confirm_leave", locale = &self.locale), Style::default().add_modifier(Modifier::BOLD)))];
+            f.render_widget(layout::get_par_with_borders(text), layout::get_default_column(area));
+            return;
+        }
+        let navbar_e = navbar::get_elements(items, self.locale.clone());
+        let navbar = layout::get_navbar(navbar_e);
+        let navbar_area = layout::get_default_column(area);
+
+        f.render_widget(navbar, navbar_area);
+    }
+
     fn render_test_name<B: Backend>(&mut self, f: &mut Frame<B>, area: Rect) {
         let title = self.item.as_ref().map_or("42", |i| i.title.as_str());
         let text = vec![
@@ -219,11 +820,19 @@ impl Runner {
 
     fn render_start_area<B: Backend>(&mut self, f: &mut Frame<B>, area: Rect) {
         let start_area = layout::get_default_column(area);
-        let layout = layout::get_header_navbar_layout(start_area, 4, 3);
-
+        let layout = layout::get_header_navbar_layout(start_area, 5, 3);
+
+        let (start, questions) = match self.progress {
+            Some(ref p) => (t!("runner.resume", locale = &self.locale,
+                current = (p.result.answers.len() + 1).min(p.question_order.len()), count = p.question_order.len()).to_string(),
+                String::new()),
+            None => (t!("runner.start", locale = &self.locale).to_string(),
+                t!("runner.sample", locale = &self.locale, count = self.sample_size, total = self.pool_size).to_string()),
+        };
         let instruction = vec![
             Spans::from(Span::raw("")),
-            Spans::from(Span::raw(t!("runner.start", locale = &self.locale))),
+            Spans::from(Span::raw(start)),
+            Spans::from(Span::raw(questions)),
             Spans::from(vec![
                 Span::styled(t!("runner.note.l1", locale = &self.locale), Style::default().add_modifier(Modifier::BOLD)),
                 Span::raw(t!("runner.note.l2", locale = &self.locale)),
@@ -232,7 +841,7 @@ impl Runner {
         let instruction_p = layout::get_par_with_colors(instruction, Color::White, Color::Blue);
         f.render_widget(instruction_p, layout[0]);
 
-        let start_e = navbar::get_elements(vec![NavType::Start, NavType::Back, NavType::Quit], self.locale.clone());
+        let start_e = navbar::get_elements(vec![NavType::Start, NavType::Practice, NavType::Back, NavType::Quit], self.locale.clone());
         let start_buttons = layout::get_test_start_row(start_e);
         f.render_widget(start_buttons, layout[1]);
     }
@@ -240,8 +849,35 @@ impl Runner {
     fn render_question<B: Backend>(&mut self, f: &mut Frame<B>, area: Rect) {
         let q_time = self.timer_q.elapsed().as_secs();
         let t_time = self.timer_t.elapsed().as_secs();
+        let q_clock = match self.get_question_limit() {
+            Some(limit) => Clock::Remaining(limit.saturating_sub(q_time)),
+            None => Clock::Elapsed(q_time),
+        };
+        let t_clock = match self.get_time_limit() {
+            Some(limit) => Clock::Remaining(limit.saturating_sub(t_time)),
+            None => Clock::Elapsed(t_time),
+        };
+        let mut hints = match self.current_question().kind {
+            QuestionKind::Multiple => vec![t!("question.multiple", locale = &self.locale).to_string()],
+            QuestionKind::Typed => vec![t!("question.typed", locale = &self.locale).to_string()],
+            QuestionKind::Cloze => vec![t!("question.cloze", locale = &self.locale,
+                    current = self.current_q_gap + 1, count = self.current_question().gaps.len()).to_string()],
+            QuestionKind::Single => vec![],
+        };
+        if self.flagged[self.current_q_number - 1] {
+            hints.push(t!("question.flagged", locale = &self.locale).to_string());
+        }
+        if self.show_feedback {
+            hints.push(t!("question.feedback", locale = &self.locale).to_string());
+        }
+        let text = match self.current_question().kind {
+            QuestionKind::Cloze if self.show_feedback => self.answers[self.current_q_number - 1].as_ref()
+                .map_or_else(|| self.cloze_text(), |a| a.filled_question()),
+            QuestionKind::Cloze => self.cloze_text(),
+            _ => self.current_q_text.clone(),
+        };
         let question_l = layout::get_question_area(
-            &self.current_q_text, self.current_q_number, self.question_count, q_time, t_time, &self.locale,
+            &text, hints, self.current_q_number, self.question_count, q_clock, t_clock, &self.locale,
             ); 
 
         let q_area = layout::get_column_with_margin(area, 30, 150);
@@ -250,7 +886,28 @@ impl Runner {
     }
 
     fn render_answers<B: Backend>(&mut self, f: &mut Frame<B>, area: Rect) {
-        let answers_l = layout::create_navigable_list(self.current_q_answers.items.clone());
+        if self.show_feedback && let Some(answer) = self.answers[self.current_q_number - 1].as_ref() {
+            let feedback = layout::get_par_default(review::get_answer_spans(answer, &self.locale));
+            let a_area = layout::get_column_with_margin(area, 30, 150);
+
+            f.render_widget(feedback, a_area);
+            return;
+        }
+        if self.is_typing() {
+            let input = layout::get_typed_answer_area(&self.current_q_typed);
+            let a_area = layout::get_column_with_margin(area, 30, 150);
+
+            f.render_widget(input, a_area);
+            return;
+        }
+        let mut items = self.current_q_answers.items.clone();
+        if self.current_question().kind == QuestionKind::Multiple {
+            items = items.into_iter()
+                .enumerate()
+                .map(|(idx, a)| layout::get_checkbox_item(a, self.current_q_checked.contains(&idx)))
+                .collect();
+        }
+        let answers_l = layout::create_navigable_list(items);
         let a_area = layout::get_column_with_margin(area, 30, 150);
 
         f.render_stateful_widget(answers_l, a_area, &mut self.current_q_answers.state);
@@ -270,18 +927,97 @@ impl Runner {
     }
 
     fn render_summary_navbar<B: Backend>(&mut self, f: &mut Frame<B>, area: Rect) {
-        let navbar_e = navbar::get_elements(vec![NavType::Details, NavType::Back, NavType::Quit], self.locale.clone());
-        let navbar = layout::get_navbar(navbar_e);
-        let navbar_area = layout::get_default_column(area);
-
-        f.render_widget(navbar, navbar_area);
+        let mut items = vec![NavType::Details, NavType::Back, NavType::Quit];
+        if self.result.can_retry() {
+            items.insert(1, NavType::Retry);
+        }
+        self.render_navbar(f, area, items);
     }
 
     fn render_summary_body<B: Backend>(&mut self, f: &mut Frame<B>, area: Rect) {
-        let table = layout::render_summary_table(self.result.clone().answers, &self.locale);
-        let table_area = layout::get_default_column(area);
+        let layout = layout::get_header_body_layout(layout::get_default_column(area), 2);
+        if let Some(grade) = self.result.grade.as_ref() {
+            let grade_p = layout::get_par_default(review::get_grade_spans(grade, &self.locale));
+            f.render_widget(grade_p, layout[0]);
+        }
 
-	f.render_widget(table, table_area);
+        let table = layout::render_summary_table(self.result.clone().answers, &self.locale);
+	f.render_widget(table, layout[1]);
     }
 }
 
+
+#[cfg(test)]
+mod units {
+    use super::*;
+
+    fn running(questions: Vec<QuestionModel>) -> Runner {
+        let mut runner = Runner::new(Some(TestModel::new(String::from("1"), String::from("Timed"), questions)), String::from("en"));
+        runner.start_test(false);
+        runner
+    }
+
+    #[test]
+    fn test_untouched_question_timed_out_unanswered() {
+        let single = QuestionModel::new(String::from("Go"), QuestionKind::Single, vec![String::from("went"), String::from("goed")], vec![1]);
+        let mut runner = running(vec![single.clone(), single]);
+
+        runner.time_out();
+        runner.go_to(2);
+        runner.handle_key_code(KeyCode::Down);
+        runner.time_out();
+
+        let untouched = runner.answers[0].clone().unwrap();
+        assert!(untouched.timed_out);
+        assert!(!untouched.is_answered());
+        assert_eq!(runner.answers[1].clone().unwrap().given, vec![1]);
+    }
+
+    #[test]
+    fn test_leaving_retry_asks_first() {
+        let single = QuestionModel::new(String::from("Go"), QuestionKind::Single, vec![String::from("went"), String::from("goed")], vec![0]);
+        let mut runner = running(vec![single.clone(), single]);
+        runner.retry_of = Some(String::from("3"));
+
+        assert_eq!(runner.handle_key_code(KeyCode::Esc).0, ScreenType::Runner);
+        assert_eq!(runner.handle_key_code(KeyCode::Down).0, ScreenType::Runner);
+        assert_eq!(runner.handle_key_code(KeyCode::Esc).0, ScreenType::Runner);
+        assert_eq!(runner.handle_key_code(KeyCode::Esc).0, ScreenType::Pause);
+    }
+
+    #[test]
+    fn test_resume_matches_answers_by_question() {
+        let mut questions = vec![
+            QuestionModel::new(String::from("Go"), QuestionKind::Single, vec![String::from("went"), String::from("goed")], vec![0]),
+            QuestionModel::new(String::from("Be"), QuestionKind::Single, vec![String::from("was"), String::from("beed")], vec![0]),
+        ];
+        // a retry keeps the numbers of the full test
+        for (idx, q) in questions.iter_mut().enumerate() {
+            q.id = format!("{}", idx + 7);
+            q.number = idx + 4;
+        }
+        let answer = AnswerModel::new(&questions[1], vec![0], 3);
+        let mut runner = Runner::new(Some(TestModel::new(String::from("1"), String::from("Paused"), questions)), String::from("en"));
+        runner.set_progress(Some(ProgressModel::new(ResultModel::new(String::from("1"), String::from("Paused"), vec![answer], 3), vec![1, 0], 0)));
+        runner.start_test(false);
+
+        assert_eq!(runner.answers[0].as_ref().map(|a| a.question_id.as_str()), Some("8"));
+        assert!(runner.answers[1].is_none());
+        assert_eq!(runner.current_q_number, 2);
+    }
+
+    #[test]
+    fn test_cloze_timed_out_keeps_entered_gaps() {
+        let gap = || test::GapModel::new(false, vec![String::from("was"), String::from("were")], vec![0]);
+        let cloze = QuestionModel::cloze(String::from("I {1} and you {2}"), vec![gap(), gap()]);
+        let mut runner = running(vec![cloze.clone(), cloze]);
+
+        runner.handle_key_code(KeyCode::Enter);
+        runner.time_out();
+
+        let answer = runner.answers[0].clone().unwrap();
+        assert!(answer.timed_out);
+        assert_eq!(answer.gaps.iter().map(|g| g.given).collect::<Vec<Option<usize>>>(), vec![Some(0), None]);
+        assert_eq!(answer.score, 0.5);
+    }
+}
@@ -0,0 +1,115 @@
+use crate::app::ScreenType;
+use crate::ui::{charts, layout, navbar, navbar::NavType};
+use crate::models::stats::StatsModel;
+
+use tui::{
+    backend::Backend,
+    layout::{Rect},
+    style::{Modifier, Style},
+    text::{Span, Spans},
+    widgets::Clear,
+    Frame,
+};
+use crossterm::event::{KeyCode};
+use rust_i18n::t;
+
+pub struct Stats {
+    pub first_render: bool,
+    pub locale: String,
+    item: StatsModel,
+}
+
+impl Stats {
+    pub fn new(item: StatsModel, locale: String) -> Self {
+        Stats { first_render: true, locale, item }
+    }
+
+    pub fn draw<B: Backend>(&mut self, f: &mut Frame<B>) {
+        if self.first_render {
+            self.first_render = false;
+            f.render_widget(Clear, f.size());
+            return;
+        }
+
+        let background = layout::get_background();
+        f.render_widget(background, f.size());
+
+        let layout = layout::get_header_navbar_layout(f.size(), 3, 3);
+
+        self.render_header(f, layout[0]);
+        self.render_navbar(f, layout[1]);
+        if self.item.attempt_count() == 0 {
+            self.render_empty(f, layout[2]);
+        } else {
+            self.render_body(f, layout[2]);
+        }
+    }
+
+    pub fn handle_key_code(&mut self, code: KeyCode) -> ScreenType {
+        match code {
+            KeyCode::Char('b') | KeyCode::Char('B') => ScreenType::Home,
+            _ => ScreenType::Stats,
+        }
+    }
+
+    fn render_header<B: Backend>(&mut self, f: &mut Frame<B>, area: Rect) {
+        let text = vec![
+            Spans::from(Span::raw("")),
+            Spans::from(vec![
+                Span::styled(t!("name.short", locale = &self.locale), Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(" - "),
+                Span::raw(t!("title.stats", locale = &self.locale)),
+            ]),
+        ];
+        let header = layout::get_header(text);
+        let header_area = layout::get_default_column(area);
+
+        f.render_widget(header, header_area);
+    }
+
+    fn render_navbar<B: Backend>(&mut self, f: &mut Frame<B>, area: Rect) {
+        let navbar_e = navbar::get_elements(vec![NavType::Back, NavType::Quit], self.locale.clone());
+        let navbar = layout::get_navbar(navbar_e);
+        let navbar_area = layout::get_default_column(area);
+
+        f.render_widget(navbar, navbar_area);
+    }
+
+    fn render_body<B: Backend>(&mut self, f: &mut Frame<B>, area: Rect) {
+        let area = layout::get_default_column(area);
+        let rows = layout::get_header_body_layout(area, 2);
+        let charts_tables = layout::get_two_row_layout(rows[1], 40);
+        let charts_area = layout::get_two_column_layout(charts_tables[0], 50);
+        let tables_area = layout::get_two_column_layout(charts_tables[1], 55);
+
+        let summary = vec![Spans::from(Span::raw(t!("stats.summary", locale = &self.locale,
+            count = self.item.attempt_count(), time = format!("{:.1}", self.item.average_time))))];
+        f.render_widget(layout::get_par_default(summary), rows[0]);
+
+        let per_day = self.item.attempts_per_day.iter()
+            .map(|(day, count)| (day.as_str(), *count))
+            .collect::<Vec<(&str, u64)>>();
+        f.render_widget(charts::get_attempts_chart(&per_day, &self.locale), charts_area[0]);
+        f.render_widget(charts::get_score_trend(&self.item.score_trend, &self.locale), charts_area[1]);
+        f.render_widget(charts::get_hardest_table(&self.item.hardest, &self.locale), tables_area[0]);
+        // tags only get a table when some of the answered questions have them
+        if self.item.tags.is_empty() {
+            f.render_widget(charts::get_exams_table(&self.item.exams, &self.locale), tables_area[1]);
+        } else {
+            let exams_tags = layout::get_two_row_layout(tables_area[1], 50);
+            f.render_widget(charts::get_exams_table(&self.item.exams, &self.locale), exams_tags[0]);
+            f.render_widget(charts::get_tags_table(&self.item.tags, &self.locale), exams_tags[1]);
+        }
+    }
+
+    fn render_empty<B: Backend>(&mut self, f: &mut Frame<B>, area: Rect) {
+        let text = vec![
+            Spans::from(Span::raw("")),
+            Spans::from(Span::styled(t!("stats.empty", locale = &self.locale), Style::default().add_modifier(Modifier::ITALIC))),
+        ];
+        let empty = layout::get_par_default(text);
+        let empty_area = layout::get_default_column(area);
+
+        f.render_widget(empty, empty_area);
+    }
+}
@@ -1,5 +1,5 @@
 use crate::app::ScreenType;
-use crate::ui::{layout, menu::Menu, navbar, navbar::NavType};
+use crate::ui::{filter::TagFilter, layout, menu::Menu, navbar, navbar::NavType};
 
 use tui::{
     backend::Backend,
@@ -17,23 +17,26 @@ pub struct Tests {
     pub locale: String,
     items: Vec<(String, String)>,
     list: Menu,
+    // ids of tests with a paused attempt
+    paused: Vec<String>,
+    filter: TagFilter,
 }
 
 impl Tests {
     pub fn new(items: Vec<(String, String)>, locale: String) -> Self {
-        let names: Vec<String> = items.iter()
-            .map(|t| t.1.clone())
-            .collect();
-        Tests { first_render: true, locale: locale, items: items, list: Menu::new(names) }
+        let mut tests = Tests { first_render: true, locale, items, list: Menu::new(vec![]), paused: vec![], filter: TagFilter::new(vec![]) };
+        tests.update_list();
+        tests
     }
 
     pub fn update_items(&mut self, items: Vec<(String, String)>) {
-        let names: Vec<String> = items.iter()
-            .map(|t| t.1.clone())
-            .collect();
-
         self.items = items;
-        self.list = Menu::new(names);
+        self.update_list();
+    }
+
+    pub fn set_tags(&mut self, tagged: Vec<(String, String)>) {
+        self.filter.set_tagged(tagged);
+        self.update_list();
     }
 
     pub fn draw<B: Backend>(&mut self, f: &mut Frame<B>) {
@@ -50,7 +53,7 @@ impl Tests {
 
         self.render_header(f, layout[0]);
         self.render_navbar(f, layout[1]);
-        if self.items.len() == 0 {
+        if self.items.is_empty() {
             self.render_empty_items(f, layout[2]);
         } else {
             self.render_test_items(f, layout[2]);
@@ -63,14 +66,40 @@ impl Tests {
             KeyCode::Up => self.list.previous(),
             KeyCode::Down => self.list.next(),
             KeyCode::Enter => return self.handle_enter(),
+            KeyCode::Char('g') | KeyCode::Char('G') => {
+                self.filter.next();
+                self.update_list();
+            },
             _ => {}
         } 
         (ScreenType::Tests, String::from("")) 
     }
 
+    pub fn mark_paused(&mut self, paused: &[String]) {
+        self.paused = paused.to_vec();
+        self.update_list();
+    }
+
+    fn visible(&self) -> Vec<&(String, String)> {
+        self.items.iter()
+            .filter(|t| self.filter.matches(&t.0))
+            .collect()
+    }
+
+    // tests with a paused attempt get a note next to the name
+    fn update_list(&mut self) {
+        let names: Vec<String> = self.visible().iter()
+            .map(|t| match self.paused.contains(&t.0) {
+                true => format!("{} {}", t.1, t!("tests.paused", locale = &self.locale)),
+                false => t.1.clone(),
+            })
+            .collect();
+        self.list = Menu::new(names);
+    }
+
     fn handle_enter(&mut self) -> (ScreenType, String) {
         match self.list.state.selected() {
-            Some(idx) => (ScreenType::Runner, self.items[idx].0.to_string()),
+            Some(idx) => (ScreenType::Runner, self.visible()[idx].0.to_string()),
             None => (ScreenType::Tests, String::from(""))
         }
     }
@@ -81,7 +110,8 @@ impl Tests {
             Spans::from(vec![
                 Span::styled(t!("name.short", locale = &self.locale), Style::default().add_modifier(Modifier::BOLD)),
                 Span::raw(" - "),
-                Span::raw(t!("title.tests", locale = &self.locale))
+                Span::raw(t!("title.tests", locale = &self.locale)),
+                Span::raw(self.filter_label()),
             ]),
         ];
         let header = layout::get_header(text);
@@ -90,8 +120,19 @@ impl Tests {
         f.render_widget(header, header_area);
     }
 
+    fn filter_label(&self) -> String {
+        match &self.filter.current {
+            Some(tag) => format!(" {}", t!("tests.filter", locale = &self.locale, tag = tag)),
+            None => String::new(),
+        }
+    }
+
     fn render_navbar<B: Backend>(&mut self, f: &mut Frame<B>, area: Rect) {
-        let navbar_e = navbar::get_elements(vec![NavType::Back, NavType::Quit], self.locale.clone());
+        let mut items = vec![NavType::Back, NavType::Quit];
+        if !self.filter.is_empty() {
+            items.insert(0, NavType::Filter);
+        }
+        let navbar_e = navbar::get_elements(items, self.locale.clone());
         let navbar = layout::get_navbar(navbar_e);
         let navbar_area = layout::get_default_column(area);
 
@@ -2,4 +2,9 @@ pub mod testservice;
 pub mod dbservice;
 pub mod configservice;
 pub mod ioservice;
+pub mod parser;
+pub mod markdown;
+pub mod exchange;
+pub mod spreadsheet;
+pub mod migrations;
 
@@ -1,23 +1,102 @@
+use crate::models::matching::MatchOptions;
 use serde::{ Serialize, Deserialize };
+use directories::ProjectDirs;
+use std::path::PathBuf;
+
+const APP_NAME: &str = "ltr-app";
+const DB_FILE: &str = "ltr.db";
 
 #[derive(Debug, Serialize, Deserialize)]
+#[serde(default)]
 struct AppConfig {
     lang: String,
+    db_path: Option<String>,
+    // matching of typed answers
+    ignore_case: bool,
+    ignore_diacritics: bool,
+    max_typos: usize,
+    // randomise question and answer order, tests can override it
+    shuffle: bool,
+    // time limits in seconds, 0 means no limit
+    question_limit: u64,
+    time_limit: u64,
+    // 'none', 'letters', 'polish' or bands like '90=A, 75=B, 0=C', tests can override it
+    grade_scale: String,
 }
 
 impl ::std::default::Default for AppConfig {
-    fn default() -> Self { Self { lang: String::from("en") } }
+    fn default() -> Self {
+        let matching = MatchOptions::default();
+        Self {
+            lang: String::from("en"),
+            db_path: None,
+            ignore_case: matching.ignore_case,
+            ignore_diacritics: matching.ignore_diacritics,
+            max_typos: matching.max_typos,
+            shuffle: false,
+            question_limit: 0,
+            time_limit: 0,
+            grade_scale: String::from("none"),
+        }
+    }
 }
 
+fn load() -> AppConfig {
+    confy::load(APP_NAME, None).unwrap_or_default()
+}
 
 pub fn get_locale() -> String {
-    let cfg: AppConfig = confy::load("ltr-app", None).unwrap();
+    let cfg = load();
 
     cfg.lang.clone()
 }
 
 pub fn save_locale(lang: String) {
-    let cfg: AppConfig = AppConfig { lang };
-    confy::store("ltr-app", None, cfg).unwrap();
+    let mut cfg = load();
+    cfg.lang = lang;
+    confy::store(APP_NAME, None, cfg).unwrap();
+}
+
+pub fn get_match_options() -> MatchOptions {
+    let cfg = load();
+
+    MatchOptions {
+        ignore_case: cfg.ignore_case,
+        ignore_diacritics: cfg.ignore_diacritics,
+        max_typos: cfg.max_typos,
+    }
+}
+
+pub fn get_shuffle() -> bool {
+    load().shuffle
 }
 
+// per question and whole test limits
+pub fn get_time_limits() -> (u64, u64) {
+    let cfg = load();
+
+    (cfg.question_limit, cfg.time_limit)
+}
+
+pub fn get_grade_scale() -> String {
+    load().grade_scale
+}
+
+// the path given on the command line wins over the one from config,
+// when none is set the database lives in the user data dir
+pub fn get_db_path(cli_path: Option<PathBuf>) -> PathBuf {
+    if let Some(path) = cli_path {
+        return path;
+    }
+    if let Some(path) = load().db_path {
+        return PathBuf::from(path);
+    }
+    default_db_path()
+}
+
+fn default_db_path() -> PathBuf {
+    match ProjectDirs::from("", "", APP_NAME) {
+        Some(dirs) => dirs.data_dir().join(DB_FILE),
+        None => PathBuf::from(DB_FILE),
+    }
+}
@@ -1,7 +1,8 @@
-use crate::models::test;
+use crate::models::{matching::MatchKind, schedule, scoring, stats, test};
+use super::migrations;
 use rusqlite::Connection;
 use std::time::{Duration, SystemTime};
-use chrono::Utc;
+use chrono::{DateTime, Local, Utc};
 
 
 #[derive(Debug)]
@@ -38,55 +39,123 @@ impl TestE {
     }
 }
 
+#[derive(Debug)]
+struct AttemptE {
+    id: usize,
+    finished: i64,
+    score: usize,
+    count: usize,
+    total_time: u64,
+    mode: String,
+    parent: Option<usize>,
+    grade: Option<String>,
+}
+
+impl AttemptE {
+    fn get_short(&self) -> (String, String, bool, bool) {
+        let finished = DateTime::from_timestamp(self.finished, 0)
+            .map(|d| d.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_default();
+        let time = format!("{:02}:{:02}", self.total_time / 60, self.total_time % 60);
+        let grade = self.grade.as_ref().map(|g| format!(" {}", g)).unwrap_or_default();
+        (format!("{}", self.id), format!("[{}] {}/{} ({}){}", finished, self.score, self.count, time, grade),
+            self.mode == "PRACTICE", self.parent.is_some())
+    }
+}
+
 #[derive(Debug, Clone)]
 struct QuestionE {
+    id: usize,
+    number: usize,
     text: String,
-    a1: String,
-    a2: String,
-    a3: String,
-    a4: String,
-    correct: usize,
+    kind: String,
+    explanation: String,
+    points: f64,
+    answers: Vec<String>,
+    correct: Vec<usize>,
+    gaps: Vec<test::GapModel>,
+    tags: Vec<String>,
 }
 
 impl QuestionE {
     fn to_model(&self) -> test::QuestionModel {
-        test::QuestionModel::new(
+        let mut question = test::QuestionModel::new(
             self.text.clone(),
-            vec![self.a1.clone(), self.a2.clone(), self.a3.clone(), self.a4.clone()],
-            self.correct as u8,
-        )
+            test::QuestionKind::from_code(&self.kind),
+            self.answers.clone(),
+            self.correct.clone(),
+        );
+        question.id = format!("{}", self.id);
+        question.number = self.number;
+        question.gaps = self.gaps.clone();
+        question.explanation = self.explanation.clone();
+        question.points = self.points;
+        question.tags = self.tags.clone();
+        question
+    }
+
+    fn load_answers(&mut self, conn: &Connection) {
+        let (answers, correct) = get_answers(conn, self.id, 0);
+        self.answers = answers;
+        self.correct = correct;
+        self.gaps = get_gaps(conn, self.id);
+        self.tags = get_question_tags(conn, self.id);
     }
 }
 
 #[derive(Debug, Clone)]
 struct AnswerE {
-    given: usize,
+    id: usize,
+    timed_out: bool,
+    chosen: String,
+    typed: String,
+    matched: String,
     time: usize,
-    text: String,
-    a1: String,
-    a2: String,
-    a3: String,
-    a4: String,
-    correct: usize,
+    question: QuestionE,
 }
 
 impl AnswerE {
-    fn to_model(&self) -> test::AnswerModel {
-        let mut g = Some(self.given);
-        if self.given == 42 {
-            g = None;
-        }
-        test::AnswerModel::new(
-            self.text.clone(),
-            vec![self.a1.clone(), self.a2.clone(), self.a3.clone(), self.a4.clone()],
-            self.correct as u8,
-            g,
-            self.given == self.correct,
-            self.time as u64,
-        )
+    fn to_model(&self, conn: &Connection) -> test::AnswerModel {
+        let question = self.question.to_model();
+        let mut answer = match question.kind {
+            test::QuestionKind::Cloze => test::AnswerModel::cloze(
+                &question,
+                get_result_gaps(conn, self.id, &question.gaps),
+                self.time as u64,
+            ),
+            test::QuestionKind::Typed => test::AnswerModel::with_match(
+                &question,
+                self.typed.clone(),
+                decode_chosen(&self.chosen).first().copied(),
+                MatchKind::from_code(&self.matched),
+                self.time as u64,
+            ),
+            _ => test::AnswerModel::new(
+                &question,
+                decode_chosen(&self.chosen),
+                self.time as u64,
+            ),
+        };
+        answer.timed_out = self.timed_out;
+        answer
     }
 }
 
+// chosen answer indices are kept as a comma separated list, empty when not answered,
+// the order of questions in a paused attempt is kept the same way
+fn encode_chosen(given: &[usize]) -> String {
+    given.iter()
+        .map(|g| g.to_string())
+        .collect::<Vec<String>>()
+        .join(",")
+}
+
+fn decode_chosen(chosen: &str) -> Vec<usize> {
+    chosen.split(',')
+        .filter_map(|c| c.trim().parse::<usize>().ok())
+        .collect()
+}
+
 pub fn get_by_status(conn: &Connection, status: &str) -> Result<Vec<(String, String)>, rusqlite::Error> {
     let select = "SELECT id, name, date FROM exam WHERE status = :status";
     let mut stmt = conn.prepare(select)?;
@@ -107,77 +176,224 @@ pub fn get_by_status(conn: &Connection, status: &str) -> Result<Vec<(String, Str
 }
 
 pub fn get_test_by_id(conn: &Connection, id: String) -> Option<test::TestModel> {
-    let stmt_t = conn.prepare("SELECT id, name, date FROM exam WHERE exam.id = :id");
-    let row = stmt_t.expect("WHAT").query_row([id.as_str()], |row| Ok(
+    let stmt_t = conn.prepare("SELECT id, name, date, shuffle, question_limit, time_limit, negative, pass_threshold, grade_scale , sample_size, quotas FROM exam WHERE exam.id = :id");
+    let row = stmt_t.expect("WHAT").query_row([id.as_str()], |row| Ok((
         TestE {
             id: row.get(0)?,
             name: row.get(1)?,
             date: row.get(2)?,
-        }
-    ));
+        },
+        row.get::<_, Option<bool>>(3)?,
+        (row.get::<_, Option<u64>>(4)?, row.get::<_, Option<u64>>(5)?),
+        (row.get::<_, Option<f64>>(6)?, row.get::<_, Option<f64>>(7)?, row.get::<_, Option<String>>(8)?),
+        (row.get::<_, Option<usize>>(9)?, row.get::<_, String>(10)?),
+    )));
 
     if row.is_err() {
         return None;
     }
 
     // TODO learn why this has to be done that way and how to correct it
-    let stmt_q = conn.prepare("SELECT text, a1, a2, a3, a4, correct FROM question WHERE examid = :id ORDER BY number ASC");
+    let stmt_q = conn.prepare("SELECT id, text, kind, explanation, number, points FROM question WHERE examid = :id ORDER BY number ASC");
     let mut binding = stmt_q.expect("WHAT");
     let rows = binding.query_map([id.as_str()], |row| {Ok(
             QuestionE {
-                text: row.get(0)?,
-                a1: row.get(1)?,
-                a2: row.get(2)?,
-                a3: row.get(3)?,
-                a4: row.get(4)?,
-                correct: row.get(5)?,
+                id: row.get(0)?,
+                text: row.get(1)?,
+                kind: row.get(2)?,
+                explanation: row.get(3)?,
+                number: row.get(4)?,
+                points: row.get(5)?,
+                answers: vec![],
+                correct: vec![],
+                gaps: vec![],
+                tags: vec![],
             }
     )}).unwrap();
     let mut questions: Vec<test::QuestionModel> = vec![];
     for row in rows {
-        let _ = row.map(|r| questions.push(r.to_model()));
+        let _ = row.map(|mut r| {
+            r.load_answers(conn);
+            questions.push(r.to_model())
+        });
     }
 
-    Some(row.unwrap().to_model(questions))
+    let (test_e, shuffle, (question_limit, time_limit), (negative, pass_threshold, grade_scale), (sample_size, quotas)) = row.unwrap();
+    let mut model = test_e.to_model(questions);
+    model.shuffle = shuffle;
+    model.question_limit = question_limit;
+    model.time_limit = time_limit;
+    model.negative = negative;
+    model.pass_threshold = pass_threshold;
+    model.grade_scale = grade_scale;
+    model.sample_size = sample_size;
+    model.quotas = test::parse_quotas(&quotas);
+    model.tags = get_exam_own_tags(conn, &model.id).unwrap_or_default();
+    Some(model)
 }
 
 pub fn get_result_by_id(conn: &Connection, id: String) -> Option<test::ResultModel> {
-    let stmt_t = conn.prepare("SELECT id, name, date FROM exam WHERE exam.id = :id");
-    let row = stmt_t.expect("WHAT").query_row([id.as_str()], |row| Ok(
+    let latest = conn.query_row(
+        "SELECT id FROM attempt WHERE examid = :id AND status = 'FINISHED' AND mode = 'EXAM' AND parent IS NULL ORDER BY finished DESC, id DESC LIMIT 1",
+        [id.as_str()],
+        |r| r.get::<_, usize>(0),
+    );
+
+    match latest {
+        Ok(attempt_id) => get_result_by_attempt(conn, format!("{}", attempt_id)),
+        Err(_) => None,
+    }
+}
+
+pub fn get_result_by_attempt(conn: &Connection, attempt_id: String) -> Option<test::ResultModel> {
+    let stmt_t = conn.prepare("SELECT exam.id, exam.name, exam.date, attempt.total_time, attempt.mode, attempt.parent, 
+        attempt.points, attempt.max_points, attempt.passed, attempt.grade FROM attempt 
+        JOIN exam ON exam.id = attempt.examid WHERE attempt.id = :id");
+    let row = stmt_t.expect("WHAT").query_row([attempt_id.as_str()], |row| Ok((
         TestE {
             id: row.get(0)?,
             name: row.get(1)?,
             date: row.get(2)?,
-        }
-    ));
+        },
+        row.get::<_, u64>(3)?,
+        row.get::<_, String>(4)?,
+        row.get::<_, Option<usize>>(5)?,
+        (row.get::<_, Option<f64>>(6)?, row.get::<_, Option<f64>>(7)?, row.get::<_, Option<bool>>(8)?, row.get::<_, Option<String>>(9)?),
+    )));
 
     if row.is_err() {
         return None;
     }
 
-    let stmt_q = conn.prepare("SELECT result.given, result.time, question.text, question.a1, question.a2, question.a3, question.a4, question.correct 
-        FROM result JOIN question ON result.qnumber = question.number WHERE question.examid = :id AND
-        result.date = (SELECT MAX(date) FROM result GROUP BY examid HAVING examid = :id)");
+    let stmt_q = conn.prepare("SELECT result.id, result.chosen, result.typed, result.matched, result.time, question.id, question.text, question.kind, question.explanation, question.number, result.timed_out, question.points 
+        FROM result JOIN question ON result.qnumber = question.number AND result.examid = question.examid 
+        WHERE result.attemptid = :id ORDER BY result.qnumber ASC");
     let mut binding = stmt_q.expect("WHAT");
-    let rows = binding.query_map([id.as_str()], |row| {Ok(
+    let rows = binding.query_map([attempt_id.as_str()], |row| {Ok(
             AnswerE {
-                given: row.get(0)?,
-                time: row.get(1)?,
-                text: row.get(2)?,
-                a1: row.get(3)?,
-                a2: row.get(4)?,
-                a3: row.get(5)?,
-                a4: row.get(6)?,
-                correct: row.get(7)?,
+                id: row.get(0)?,
+                timed_out: row.get(10)?,
+                chosen: row.get(1)?,
+                typed: row.get(2)?,
+                matched: row.get(3)?,
+                time: row.get(4)?,
+                question: QuestionE {
+                    id: row.get(5)?,
+                    text: row.get(6)?,
+                    kind: row.get(7)?,
+                    explanation: row.get(8)?,
+                    number: row.get(9)?,
+                    points: row.get(11)?,
+                    answers: vec![],
+                    correct: vec![],
+                    gaps: vec![],
+                    tags: vec![],
+                },
             }
     )}).unwrap();
     let mut answers: Vec<test::AnswerModel> = vec![];
     for row in rows {
-        let _ = row.map(|r| answers.push(r.to_model()));
+        let _ = row.map(|mut r| {
+            r.question.load_answers(conn);
+            answers.push(r.to_model(conn))
+        });
+    }
+
+    let (test_e, total_time, mode, parent, (points, max_points, passed, grade)) = row.unwrap();
+    let mut result = test_e.to_result(answers, total_time);
+    result.practice = mode == "PRACTICE";
+    result.attempt = Some(attempt_id);
+    result.retry_of = parent.map(|p| format!("{}", p));
+    // attempts from before scoring was added have no points stored
+    result.grade = points.zip(max_points).map(|(points, max_points)| scoring::Grade { points, max_points, passed, grade });
+    Some(result)
+}
+
+// answers of the question itself use gap 0, cloze answers are kept under their gap number
+fn get_answers(conn: &Connection, question_id: usize, gap: usize) -> (Vec<String>, Vec<usize>) {
+    let stmt = conn.prepare("SELECT text, correct FROM answer WHERE questionid = ?1 AND gap = ?2 ORDER BY number ASC");
+    let mut binding = stmt.expect("WHAT");
+    let rows = binding.query_map([question_id, gap], |row| Ok((row.get::<_, String>(0)?, row.get::<_, bool>(1)?))).unwrap();
+    let mut answers: Vec<String> = vec![];
+    let mut correct: Vec<usize> = vec![];
+    for (idx, row) in rows.enumerate() {
+        let _ = row.map(|(text, is_correct)| {
+            if is_correct {
+                correct.push(idx);
+            }
+            answers.push(text)
+        });
+    }
+    (answers, correct)
+}
+
+fn get_question_tags(conn: &Connection, question_id: usize) -> Vec<String> {
+    let stmt = conn.prepare("SELECT tag FROM question_tag WHERE questionid = ?1 ORDER BY rowid ASC");
+    let mut binding = stmt.expect("WHAT");
+    let rows = binding.query_map([question_id], |row| row.get::<_, String>(0)).unwrap();
+    rows.filter_map(|r| r.ok()).collect()
+}
+
+fn get_gaps(conn: &Connection, question_id: usize) -> Vec<test::GapModel> {
+    let stmt = conn.prepare("SELECT number, typed FROM gap WHERE questionid = :id ORDER BY number ASC");
+    let mut binding = stmt.expect("WHAT");
+    let rows = binding.query_map([question_id], |row| Ok((row.get::<_, usize>(0)?, row.get::<_, bool>(1)?))).unwrap();
+    let mut gaps: Vec<test::GapModel> = vec![];
+    for row in rows {
+        let _ = row.map(|(number, typed)| {
+            let (answers, correct) = get_answers(conn, question_id, number);
+            gaps.push(test::GapModel::new(typed, answers, correct))
+        });
     }
-    let total_time: u64 = answers.iter().map(|a| a.time).sum();
+    gaps
+}
 
-    Some(row.unwrap().to_result(answers, total_time))
+fn get_result_gaps(conn: &Connection, result_id: usize, gaps: &[test::GapModel]) -> Vec<test::GapAnswer> {
+    let stmt = conn.prepare("SELECT number, chosen, typed, matched FROM result_gap WHERE resultid = :id ORDER BY number ASC");
+    let mut binding = stmt.expect("WHAT");
+    let rows = binding.query_map([result_id], |row| Ok((
+        row.get::<_, usize>(0)?,
+        row.get::<_, String>(1)?,
+        row.get::<_, String>(2)?,
+        row.get::<_, String>(3)?,
+    ))).unwrap();
+    let mut answers: Vec<test::GapAnswer> = vec![];
+    for row in rows {
+        let _ = row.map(|(number, chosen, typed, matched)| {
+            if let Some(gap) = gaps.get(number - 1) {
+                let given = decode_chosen(&chosen).first().copied();
+                answers.push(test::GapAnswer::with_match(gap, given, typed, MatchKind::from_code(&matched)))
+            }
+        });
+    }
+    answers
+}
+
+// id, short description and whether it was a practice attempt and a retry of another one
+pub fn get_attempts(conn: &Connection, id: String) -> Result<Vec<(String, String, bool, bool)>, rusqlite::Error> {
+    let select = "SELECT attempt.id, attempt.finished, attempt.score, attempt.total_time, 
+        (SELECT COUNT(*) FROM result WHERE result.attemptid = attempt.id), attempt.mode, attempt.parent, attempt.grade 
+        FROM attempt WHERE attempt.examid = :id AND attempt.status = 'FINISHED' ORDER BY attempt.finished DESC, attempt.id DESC";
+    let mut stmt = conn.prepare(select)?;
+
+    let rows = stmt.query_map([id.as_str()], |row| Ok(
+        AttemptE {
+            id: row.get(0)?,
+            finished: row.get(1)?,
+            score: row.get(2)?,
+            total_time: row.get(3)?,
+            count: row.get(4)?,
+            mode: row.get(5)?,
+            parent: row.get(6)?,
+            grade: row.get(7)?,
+        }
+    ))?;
+    let mut results: Vec<(String, String, bool, bool)> = Vec::new();
+    for row in rows {
+        let _ = row.map(|r| results.push(r.get_short()));
+    }
+
+    Ok(results)
 }
 
 pub fn update_status(conn: &Connection, id: String, status: &str) -> Result<(), Box<dyn std::error::Error>> {
@@ -186,90 +402,343 @@ pub fn update_status(conn: &Connection, id: String, status: &str) -> Result<(),
     Ok(())
 }
 
-pub fn save_result(conn: &Connection, result: test::ResultModel) -> Result<(), Box<dyn std::error::Error>> {
+// returns the id of the new attempt
+pub fn save_result(conn: &Connection, result: test::ResultModel) -> Result<String, Box<dyn std::error::Error>> {
     let date = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap_or(Duration::ZERO).as_secs();
-    let mut idx = 0;
-    result.answers.iter()
-        .map(|a| {
-            idx += 1;
-            (&result.id, idx, a.given.unwrap_or(42), a.time, date)
-        })
-        .for_each(|r| {
-            let _ = conn.execute(
-                "INSERT INTO result (examid, qnumber, given, time, date) 
-                VALUES (?1, ?2, ?3, ?4, ?5)",
-                (r.0, r.1, r.2, r.3, r.4),
-            );
-        });
+    let grade = result.grade.as_ref();
+    let tx = conn.unchecked_transaction()?;
+    // a finished attempt replaces the paused one it was resumed from, a retry is never paused
+    if result.retry_of.is_none() {
+        delete_progress(&tx, &result.id)?;
+    }
+    let attempt_id = tx.query_row(
+        "INSERT INTO attempt (examid, started, finished, score, total_time, mode, parent, points, max_points, passed, grade) 
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11) RETURNING id",
+        (&result.id, date.saturating_sub(result.total_time), date, result.score(), result.total_time, result.mode(), &result.retry_of,
+            grade.map(|g| g.points), grade.map(|g| g.max_points), grade.and_then(|g| g.passed), grade.and_then(|g| g.grade.clone())),
+        |r| r.get::<_, i64>(0),
+    )?;
+    insert_results(&tx, &result, attempt_id, date)?;
+    update_reviews(&tx, &result, schedule::today())?;
+    tx.commit()?;
+    Ok(format!("{}", attempt_id))
+}
+
+// a review session is not an attempt of any test, only the review state of its questions changes
+pub fn save_review(conn: &Connection, result: &test::ResultModel, today: i32) -> Result<(), Box<dyn std::error::Error>> {
+    let tx = conn.unchecked_transaction()?;
+    update_reviews(&tx, result, today)?;
+    tx.commit()?;
     Ok(())
 }
 
-pub fn save_new_test(conn: &Connection, model: &test::TestModel) -> Result<(), Box<dyn std::error::Error>> {
-    let date = Utc::now().format("%Y-%m-%d").to_string();
-    let test_t = (&model.title, date, "NOT_STARTED");
-    let mut stmt_t = conn.prepare(
-        "INSERT INTO exam (name, date, status) VALUES (?1, ?2, ?3) RETURNING id",
+fn update_reviews(conn: &Connection, result: &test::ResultModel, today: i32) -> Result<(), rusqlite::Error> {
+    for a in result.answers.iter().filter(|a| !a.question_id.is_empty()) {
+        let state = get_review(conn, &a.question_id)?.unwrap_or_default().next(schedule::quality(a), today);
+        conn.execute(
+            "INSERT INTO review (questionid, ease, interval, repetitions, due) VALUES (?1, ?2, ?3, ?4, ?5)
+            ON CONFLICT (questionid) DO UPDATE SET ease = ?2, interval = ?3, repetitions = ?4, due = ?5",
+            (&a.question_id, state.ease, state.interval, state.repetitions, state.due),
+        )?;
+    }
+    Ok(())
+}
+
+fn get_review(conn: &Connection, question_id: &str) -> Result<Option<schedule::ReviewState>, rusqlite::Error> {
+    let row = conn.query_row(
+        "SELECT ease, interval, repetitions, due FROM review WHERE questionid = ?1",
+        [question_id],
+        |r| Ok(schedule::ReviewState { ease: r.get(0)?, interval: r.get(1)?, repetitions: r.get(2)?, due: r.get(3)? }),
+    );
+    match row {
+        Ok(state) => Ok(Some(state)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+// questions of every test that are due on the given day, the longest overdue first,
+// questions never answered in a test have no review state yet and are left for the tests to introduce
+pub fn get_due_questions(conn: &Connection, today: i32) -> Result<Vec<test::QuestionModel>, rusqlite::Error> {
+    let mut stmt = conn.prepare("SELECT question.id, question.text, question.kind, question.explanation, question.number, question.points 
+        FROM review JOIN question ON question.id = review.questionid 
+        WHERE review.due <= ?1 ORDER BY review.due ASC, question.id ASC")?;
+    let rows = stmt.query_map([today], |row| Ok(
+        QuestionE {
+            id: row.get(0)?,
+            text: row.get(1)?,
+            kind: row.get(2)?,
+            explanation: row.get(3)?,
+            number: row.get(4)?,
+            points: row.get(5)?,
+            answers: vec![],
+            correct: vec![],
+            gaps: vec![],
+            tags: vec![],
+        }
+    ))?;
+    let mut questions: Vec<test::QuestionModel> = vec![];
+    for row in rows {
+        let mut r = row?;
+        r.load_answers(conn);
+        questions.push(r.to_model());
+    }
+    Ok(questions)
+}
+
+fn get_exam_own_tags(conn: &Connection, exam_id: &str) -> Result<Vec<String>, rusqlite::Error> {
+    let mut stmt = conn.prepare("SELECT tag FROM exam_tag WHERE examid = ?1 ORDER BY tag ASC")?;
+    let rows = stmt.query_map([exam_id], |row| row.get::<_, String>(0))?;
+    rows.collect()
+}
+
+// pairs of exam id and tag, an exam has its own tags and the tags of its questions
+pub fn get_exam_tags(conn: &Connection) -> Result<Vec<(String, String)>, rusqlite::Error> {
+    let mut stmt = conn.prepare("SELECT examid, tag FROM exam_tag 
+        UNION SELECT question.examid, question_tag.tag FROM question_tag JOIN question ON question.id = question_tag.questionid 
+        ORDER BY 2 ASC, 1 ASC")?;
+    let rows = stmt.query_map([], |row| Ok((format!("{}", row.get::<_, i64>(0)?), row.get::<_, String>(1)?)))?;
+    rows.collect()
+}
+
+// questions with their own tags and the tags of their exam
+const TAGGED_QUESTIONS: &str = "WITH tagged AS (SELECT questionid, tag FROM question_tag 
+    UNION SELECT question.id, exam_tag.tag FROM exam_tag JOIN question ON question.examid = exam_tag.examid) ";
+
+// every tag with the number of questions that have it
+pub fn get_tags(conn: &Connection) -> Result<Vec<(String, usize)>, rusqlite::Error> {
+    let mut stmt = conn.prepare(&format!("{}SELECT tag, COUNT(*) FROM tagged GROUP BY tag ORDER BY tag ASC", TAGGED_QUESTIONS))?;
+    let rows = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, usize>(1)?)))?;
+    rows.collect()
+}
+
+// questions of every test with the tag, ordered by test and number
+pub fn get_tagged_questions(conn: &Connection, tag: &str) -> Result<Vec<test::QuestionModel>, rusqlite::Error> {
+    let mut stmt = conn.prepare(&format!("{}SELECT question.id, question.text, question.kind, question.explanation, question.number, question.points 
+        FROM tagged JOIN question ON question.id = tagged.questionid 
+        WHERE tagged.tag = ?1 ORDER BY question.examid ASC, question.number ASC", TAGGED_QUESTIONS))?;
+    let rows = stmt.query_map([tag], |row| Ok(
+        QuestionE {
+            id: row.get(0)?,
+            text: row.get(1)?,
+            kind: row.get(2)?,
+            explanation: row.get(3)?,
+            number: row.get(4)?,
+            points: row.get(5)?,
+            answers: vec![],
+            correct: vec![],
+            gaps: vec![],
+            tags: vec![],
+        }
+    ))?;
+    let mut questions: Vec<test::QuestionModel> = vec![];
+    for row in rows {
+        let mut r = row?;
+        r.load_answers(conn);
+        questions.push(r.to_model());
+    }
+    Ok(questions)
+}
+
+pub fn count_due(conn: &Connection, today: i32) -> Result<usize, rusqlite::Error> {
+    conn.query_row("SELECT COUNT(*) FROM review WHERE due <= ?1", [today], |r| r.get::<_, usize>(0))
+}
+
+// scores of finished attempts from the oldest one, retries only cover some of the questions and are left out,
+// exams are named the same way as in the lists
+pub fn get_attempt_scores(conn: &Connection) -> Result<Vec<stats::AttemptScore>, rusqlite::Error> {
+    let mut stmt = conn.prepare("SELECT '[' || exam.date || '] ' || exam.name, attempt.finished, SUM(result.score), COUNT(result.id), 
+        attempt.points, attempt.max_points 
+        FROM result JOIN attempt ON attempt.id = result.attemptid JOIN exam ON exam.id = result.examid 
+        WHERE attempt.status = 'FINISHED' AND attempt.mode = 'EXAM' AND attempt.parent IS NULL 
+        GROUP BY attempt.id ORDER BY attempt.finished ASC, attempt.id ASC")?;
+    let rows = stmt.query_map([], |row| Ok(
+        stats::AttemptScore {
+            exam: row.get(0)?,
+            finished: row.get(1)?,
+            score: row.get(2)?,
+            count: row.get(3)?,
+            points: row.get(4)?,
+            max_points: row.get(5)?,
+        }
+    ))?;
+    rows.collect()
+}
+
+// finished answers grouped by tag, the weakest tag first
+pub fn get_tag_scores(conn: &Connection) -> Result<Vec<stats::TagStats>, rusqlite::Error> {
+    let mut stmt = conn.prepare(&format!("{}SELECT tagged.tag, COUNT(result.id), AVG(result.score) AS score 
+        FROM result JOIN attempt ON attempt.id = result.attemptid 
+        JOIN question ON question.examid = result.examid AND question.number = result.qnumber 
+        JOIN tagged ON tagged.questionid = question.id 
+        WHERE attempt.status = 'FINISHED' 
+        GROUP BY tagged.tag ORDER BY score ASC, tagged.tag ASC", TAGGED_QUESTIONS))?;
+    let rows = stmt.query_map([], |row| Ok(
+        stats::TagStats {
+            tag: row.get(0)?,
+            answered: row.get(1)?,
+            score_rate: row.get(2)?,
+        }
+    ))?;
+    rows.collect()
+}
+
+// questions with the highest share of missed score over all their finished answers
+pub fn get_hardest_questions(conn: &Connection, limit: usize) -> Result<Vec<stats::QuestionStats>, rusqlite::Error> {
+    let mut stmt = conn.prepare("SELECT '[' || exam.date || '] ' || exam.name, question.text, COUNT(result.id), 1 - AVG(result.score) AS errors 
+        FROM result JOIN attempt ON attempt.id = result.attemptid 
+        JOIN question ON question.examid = result.examid AND question.number = result.qnumber 
+        JOIN exam ON exam.id = result.examid 
+        WHERE attempt.status = 'FINISHED' 
+        GROUP BY question.id HAVING errors > 0 
+        ORDER BY errors DESC, COUNT(result.id) DESC, question.id ASC LIMIT ?1")?;
+    let rows = stmt.query_map([limit], |row| Ok(
+        stats::QuestionStats {
+            exam: row.get(0)?,
+            question: row.get(1)?,
+            answered: row.get(2)?,
+            error_rate: row.get(3)?,
+        }
+    ))?;
+    rows.collect()
+}
+
+pub fn get_average_time(conn: &Connection) -> Result<f64, rusqlite::Error> {
+    conn.query_row("SELECT COALESCE(AVG(result.time), 0.0) FROM result 
+        JOIN attempt ON attempt.id = result.attemptid WHERE attempt.status = 'FINISHED'",
+        [],
+        |r| r.get::<_, f64>(0))
+}
+
+// a test can only have one paused attempt, saving the progress replaces it
+pub fn save_progress(conn: &Connection, progress: &test::ProgressModel) -> Result<(), Box<dyn std::error::Error>> {
+    let date = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap_or(Duration::ZERO).as_secs();
+    let result = &progress.result;
+    let tx = conn.unchecked_transaction()?;
+    delete_progress(&tx, &result.id)?;
+    let attempt_id = tx.query_row(
+        "INSERT INTO attempt (examid, started, finished, score, total_time, status, question_order, question_time, mode, flagged, current_question) 
+        VALUES (?1, ?2, ?3, ?4, ?5, 'IN_PROGRESS', ?6, ?7, ?8, ?9, ?10) RETURNING id",
+        (&result.id, date.saturating_sub(result.total_time), date, result.score(), result.total_time,
+            encode_chosen(&progress.question_order), progress.question_time, result.mode(),
+            encode_chosen(&progress.flagged), progress.current),
+        |r| r.get::<_, i64>(0),
     )?;
-    let exam_id = stmt_t.query_row(test_t, |r| r.get::<_, i64>(0))?;
-
-    let mut q_num = 0;
-    model.questions.iter()
-        .map(|q| { 
-            q_num += 1;
-            (q_num, &q.question, &q.answers[0], &q.answers[1], &q.answers[2], &q.answers[3], q.correct, exam_id)
-        })
-        .for_each(|r| {
-            let _ = conn.execute(
-                "INSERT INTO question (number, text, a1, a2, a3, a4, correct, examid)
-                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
-                (r.0, r.1, r.2, r.3, r.4, r.5, r.6, r.7),
-            );
-    });
+    insert_results(&tx, result, attempt_id, date)?;
+    tx.commit()?;
+    Ok(())
+}
+
+pub fn get_progress(conn: &Connection, id: String) -> Option<test::ProgressModel> {
+    let row = conn.query_row(
+        "SELECT id, question_order, question_time, flagged, current_question FROM attempt WHERE examid = :id AND status = 'IN_PROGRESS'",
+        [id.as_str()],
+        |r| Ok((r.get::<_, usize>(0)?, r.get::<_, String>(1)?, r.get::<_, u64>(2)?, r.get::<_, String>(3)?, r.get::<_, Option<usize>>(4)?)),
+    );
+    let (attempt_id, order, question_time, flagged, current) = row.ok()?;
+    let result = get_result_by_attempt(conn, format!("{}", attempt_id))?;
+    let mut progress = test::ProgressModel::new(result, decode_chosen(&order), question_time);
+    progress.flagged = decode_chosen(&flagged);
+    progress.current = current;
+    Some(progress)
+}
 
+// ids of the tests with a paused attempt
+pub fn get_paused(conn: &Connection) -> Result<Vec<String>, rusqlite::Error> {
+    let mut stmt = conn.prepare("SELECT DISTINCT examid FROM attempt WHERE status = 'IN_PROGRESS'")?;
+    let rows = stmt.query_map([], |r| r.get::<_, usize>(0))?;
+    let mut results: Vec<String> = Vec::new();
+    for row in rows {
+        let _ = row.map(|r| results.push(format!("{}", r)));
+    }
+
+    Ok(results)
+}
+
+fn delete_progress(conn: &Connection, id: &str) -> Result<(), rusqlite::Error> {
+    let paused = "SELECT id FROM attempt WHERE examid = ?1 AND status = 'IN_PROGRESS'";
+    conn.execute(&format!("DELETE FROM result_gap WHERE resultid IN (SELECT id FROM result WHERE attemptid IN ({}))", paused), [id])?;
+    conn.execute(&format!("DELETE FROM result WHERE attemptid IN ({})", paused), [id])?;
+    conn.execute(&format!("DELETE FROM attempt WHERE id IN ({})", paused), [id])?;
     Ok(())
 }
 
-pub fn create_schema(conn: &Connection) -> Result<(), Box<dyn std::error::Error>> {
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS exam (
-            id INTEGER PRIMARY KEY,
-            name TEXT NOT NULL,
-            date TEXT NOT NULL,
-            status TEXT NOT NULL
-        ) STRICT",
-        (),
-    )?;
+// answers are stored under the question number from the test, not the order they were shown in
+fn insert_results(conn: &Connection, result: &test::ResultModel, attempt_id: i64, date: u64) -> Result<(), rusqlite::Error> {
+    for a in result.answers.iter() {
+        let result_id = conn.query_row(
+            "INSERT INTO result (examid, qnumber, chosen, typed, matched, score, time, date, attemptid, timed_out) 
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10) RETURNING id",
+            (&result.id, a.number, encode_chosen(&a.given), &a.typed, a.matched.code(), a.score, a.time, date, attempt_id, a.timed_out),
+            |r| r.get::<_, i64>(0),
+        )?;
+        for (gidx, g) in a.gaps.iter().enumerate() {
+            conn.execute(
+                "INSERT INTO result_gap (resultid, number, chosen, typed, matched) VALUES (?1, ?2, ?3, ?4, ?5)",
+                (result_id, gidx + 1, encode_chosen(&g.given.into_iter().collect::<Vec<usize>>()), &g.typed, g.matched.code()),
+            )?;
+        }
+    }
+    Ok(())
+}
 
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS question (
-          id INTEGER PRIMARY KEY,
-          number INTEGER,
-          text TEXT NOT NULL,
-          a1 TEXT NOT NULL,
-          a2 TEXT NOT NULL,
-          a3 TEXT,
-          a4 TEXT,
-          correct INTEGER NOT NULL,
-          examid INTEGER REFERENCES exam(id)
-        ) STRICT",
-        (),
+pub fn save_new_test(conn: &Connection, model: &test::TestModel) -> Result<String, Box<dyn std::error::Error>> {
+    let date = Utc::now().format("%Y-%m-%d").to_string();
+    let test_t = (&model.title, date, "NOT_STARTED", model.shuffle, model.question_limit, model.time_limit,
+        model.negative, model.pass_threshold, &model.grade_scale, model.sample_size, test::encode_quotas(&model.quotas));
+    let tx = conn.unchecked_transaction()?;
+    let exam_id = tx.query_row(
+        "INSERT INTO exam (name, date, status, shuffle, question_limit, time_limit, negative, pass_threshold, grade_scale, sample_size, quotas) 
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11) RETURNING id",
+        test_t,
+        |r| r.get::<_, i64>(0),
     )?;
 
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS result (
-            id INTEGER PRIMARY KEY,
-            examid INTEGER REFERENCES exam(id),
-            qnumber INTEGER,
-            given INTEGER,
-            time INTEGER,
-            date INTEGER
-        ) STRICT",
-        (),
+    for (idx, q) in model.questions.iter().enumerate() {
+        insert_question(&tx, exam_id, idx + 1, q)?;
+    }
+    for tag in model.tags.iter() {
+        tx.execute("INSERT OR IGNORE INTO exam_tag (examid, tag) VALUES (?1, ?2)", (exam_id, tag))?;
+    }
+    tx.commit()?;
+
+    Ok(format!("{}", exam_id))
+}
+
+fn insert_question(conn: &Connection, exam_id: i64, number: usize, q: &test::QuestionModel) -> Result<(), rusqlite::Error> {
+    let question_id = conn.query_row(
+        "INSERT INTO question (number, text, kind, explanation, examid, points) VALUES (?1, ?2, ?3, ?4, ?5, ?6) RETURNING id",
+        (number, &q.question, q.kind.code(), &q.explanation, exam_id, q.points),
+        |r| r.get::<_, i64>(0),
     )?;
+    insert_answers(conn, question_id, 0, &q.answers, &q.correct)?;
+    for tag in q.tags.iter() {
+        conn.execute("INSERT OR IGNORE INTO question_tag (questionid, tag) VALUES (?1, ?2)", (question_id, tag))?;
+    }
+    for (idx, gap) in q.gaps.iter().enumerate() {
+        conn.execute(
+            "INSERT INTO gap (questionid, number, typed) VALUES (?1, ?2, ?3)",
+            (question_id, idx + 1, gap.typed),
+        )?;
+        insert_answers(conn, question_id, idx + 1, &gap.answers, &gap.correct)?;
+    }
+    Ok(())
+}
 
+fn insert_answers(conn: &Connection, question_id: i64, gap: usize, answers: &[String], correct: &[usize]) -> Result<(), rusqlite::Error> {
+    for (idx, a) in answers.iter().enumerate() {
+        conn.execute(
+            "INSERT INTO answer (questionid, number, text, correct, gap) VALUES (?1, ?2, ?3, ?4, ?5)",
+            (question_id, idx, a, correct.contains(&idx), gap),
+        )?;
+    }
     Ok(())
 }
 
+pub fn create_schema(conn: &Connection) -> Result<(), Box<dyn std::error::Error>> {
+    migrations::migrate(conn)
+}
+
 pub fn populate_tests(conn: &Connection) {
     let data = &[
         ("English idioms with a twist", "2025-03-07", "NOT_STARTED"),
@@ -466,32 +935,45 @@ pub fn populate_questions(conn: &Connection) {
     ];
 
     data.iter().for_each(|r| {
-        let _ = conn.execute(
-            "INSERT INTO question (number, text, a1, a2, a3, a4, correct, examid)
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
-            (r.0, r.1, r.2, r.3, r.4, r.5, r.6, r.7),
-        );
+        let answers = vec![r.2.to_string(), r.3.to_string(), r.4.to_string(), r.5.to_string()];
+        let q = test::QuestionModel::new(r.1.to_string(), test::QuestionKind::Single, answers, vec![r.6]);
+        let _ = insert_question(conn, r.7, r.0, &q);
     });
 }
 
 pub fn populate_answers(conn: &Connection) {
-    let data = vec![
-        (4, 1, 1, 56, 10001),
-        (5, 1, 2, 66, 10011),
-        (5, 2, 1, 120, 10011),
-        (5, 3, 2, 61, 10011),
-        (6, 1, 2, 9, 10101),
-        (6, 2, 0, 12, 10101),
-        (7, 1, 3, 100, 11001),
-        (7, 2, 0, 23, 11001),
+    let attempts = &[
+        (4, 9945, 10001, 0, 56),
+        (5, 9764, 10011, 1, 247),
+        (6, 10080, 10101, 2, 21),
+        (7, 10878, 11001, 0, 123),
     ];
 
-    data.iter().for_each(|r| {
+    attempts.iter().for_each(|r| {
         let _ = conn.execute(
-            "INSERT INTO result (examid, qnumber, given, time, date) 
+            "INSERT INTO attempt (examid, started, finished, score, total_time) 
             VALUES (?1, ?2, ?3, ?4, ?5)",
             (r.0, r.1, r.2, r.3, r.4),
         );
     });
+
+    let data = &[
+        (4, 1, "1", 0.0, 56, 10001, 1),
+        (5, 1, "2", 0.0, 66, 10011, 2),
+        (5, 2, "1", 1.0, 120, 10011, 2),
+        (5, 3, "2", 0.0, 61, 10011, 2),
+        (6, 1, "2", 1.0, 9, 10101, 3),
+        (6, 2, "0", 1.0, 12, 10101, 3),
+        (7, 1, "3", 0.0, 100, 11001, 4),
+        (7, 2, "0", 0.0, 23, 11001, 4),
+    ];
+
+    data.iter().for_each(|r| {
+        let _ = conn.execute(
+            "INSERT INTO result (examid, qnumber, chosen, score, time, date, attemptid) 
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            (r.0, r.1, r.2, r.3, r.4, r.5, r.6),
+        );
+    });
 }
 
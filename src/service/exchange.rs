@@ -0,0 +1,344 @@
+use crate::models::test;
+use super::parser::{self, Diagnostic, Problem};
+
+use serde::{Serialize, Deserialize};
+
+// bumped whenever a field changes its meaning or goes away, new optional fields keep the version
+pub const SCHEMA_VERSION: u32 = 1;
+
+// the JSON and YAML schema of a test, version 1:
+// - schema_version: always 1
+// - title: at least 2 characters
+// - tags, shuffle, question_limit and time_limit in seconds, negative, pass_threshold in percent,
+//   grade_scale, sample_size and quotas like [{tag: verbs, count: 2}] are optional
+// - questions: at least 2 of them, each with a text, a kind (single, multiple, typed or cloze, single when left out),
+//   answers like [{text: went, correct: true}], or gaps with their own answers for cloze questions,
+//   an optional explanation, points (1 when left out) and tags
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TestDocument {
+    pub schema_version: u32,
+    pub title: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shuffle: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub question_limit: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_limit: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub negative: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pass_threshold: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub grade_scale: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sample_size: Option<usize>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub quotas: Vec<QuotaDocument>,
+    pub questions: Vec<QuestionDocument>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct QuotaDocument {
+    pub tag: String,
+    pub count: usize,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KindDocument {
+    #[default]
+    Single,
+    Multiple,
+    Typed,
+    Cloze,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct QuestionDocument {
+    pub text: String,
+    #[serde(default)]
+    pub kind: KindDocument,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub answers: Vec<AnswerDocument>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub gaps: Vec<GapDocument>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub explanation: String,
+    #[serde(default = "default_points")]
+    pub points: f64,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AnswerDocument {
+    pub text: String,
+    #[serde(default)]
+    pub correct: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GapDocument {
+    // typed gaps accept every correct answer as a spelling, the others are a dropdown
+    #[serde(default)]
+    pub typed: bool,
+    pub answers: Vec<AnswerDocument>,
+}
+
+fn default_points() -> f64 {
+    1.0
+}
+
+impl TestDocument {
+    // the stored title starts with the import date, it is dropped so the test can be imported again
+    pub fn from_model(model: &test::TestModel) -> Self {
+        let title = model.title.split_once("] ")
+            .filter(|_| model.title.starts_with('['))
+            .map(|(_, name)| name)
+            .unwrap_or(&model.title);
+        TestDocument {
+            schema_version: SCHEMA_VERSION,
+            title: title.to_string(),
+            tags: model.tags.clone(),
+            shuffle: model.shuffle,
+            question_limit: model.question_limit,
+            time_limit: model.time_limit,
+            negative: model.negative,
+            pass_threshold: model.pass_threshold,
+            grade_scale: model.grade_scale.clone(),
+            sample_size: model.sample_size,
+            quotas: model.quotas.iter().map(|(tag, count)| QuotaDocument { tag: tag.clone(), count: *count }).collect(),
+            questions: model.questions.iter().map(QuestionDocument::from_model).collect(),
+        }
+    }
+
+    // checked like the other formats, problems point at the whole file as there are no lines to report
+    pub fn into_model(self, file: &str) -> Result<test::TestModel, Vec<Diagnostic>> {
+        if self.schema_version != SCHEMA_VERSION {
+            return Err(vec![Diagnostic::new(file, 0, 0, Problem::UnsupportedSchema(self.schema_version))]);
+        }
+        let mut diagnostics = vec![];
+        let title = self.title.trim().to_string();
+        if title.chars().count() < 2 {
+            diagnostics.push(Diagnostic::new(file, 0, 0, Problem::MissingTitle));
+        }
+        for (idx, question) in self.questions.iter().enumerate() {
+            diagnostics.extend(question.kind_problems(idx + 1).into_iter().map(|p| Diagnostic::new(file, 0, 0, p)));
+            if !parser::valid_points(question.points) {
+                diagnostics.push(Diagnostic::new(file, 0, 0, Problem::InvalidOption(String::from("points"), question.points.to_string())));
+            }
+        }
+        let questions = self.questions.into_iter()
+            .map(|q| (q.into_model(), Some(0)))
+            .collect();
+
+        // scoring options are checked like the ones of the text formats
+        let options = [
+            self.negative.map(|v| ("negative", v.to_string())),
+            self.pass_threshold.map(|v| ("pass", v.to_string())),
+            self.grade_scale.map(|v| ("grades", v)),
+        ].into_iter()
+            .flatten()
+            .map(|(key, value)| (0, 0, key.to_string(), value))
+            .collect();
+
+        let mut model = parser::build_test(file, title, questions, options, diagnostics)?;
+        model.tags = test::parse_tags(&self.tags.join(","));
+        model.shuffle = self.shuffle;
+        model.question_limit = self.question_limit;
+        model.time_limit = self.time_limit;
+        model.sample_size = self.sample_size.filter(|s| *s > 0);
+        model.quotas = self.quotas.into_iter()
+            .map(|q| (q.tag.trim().to_lowercase(), q.count))
+            .filter(|(tag, _)| !tag.is_empty())
+            .collect();
+        Ok(model)
+    }
+}
+
+impl QuestionDocument {
+    fn from_model(question: &test::QuestionModel) -> Self {
+        let kind = match question.kind {
+            test::QuestionKind::Single => KindDocument::Single,
+            test::QuestionKind::Multiple => KindDocument::Multiple,
+            test::QuestionKind::Typed => KindDocument::Typed,
+            test::QuestionKind::Cloze => KindDocument::Cloze,
+        };
+        QuestionDocument {
+            text: question.question.clone(),
+            kind,
+            answers: to_answers(&question.answers, &question.correct),
+            gaps: question.gaps.iter()
+                .map(|g| GapDocument { typed: g.typed, answers: to_answers(&g.answers, &g.correct) })
+                .collect(),
+            explanation: question.explanation.clone(),
+            points: question.points,
+            tags: question.tags.clone(),
+        }
+    }
+
+    // the kind is taken as given, answers that do not fit it are reported instead of changing it
+    fn kind_problems(&self, number: usize) -> Vec<Problem> {
+        let mut problems = vec![];
+        if self.kind == KindDocument::Single && self.answers.iter().filter(|a| a.correct).count() > 1 {
+            problems.push(Problem::TooManyCorrect(number));
+        }
+        if self.kind == KindDocument::Cloze && !self.answers.is_empty() {
+            problems.push(Problem::UnexpectedAnswers(number));
+        }
+        if self.kind != KindDocument::Cloze && !self.gaps.is_empty() {
+            problems.push(Problem::UnexpectedGaps(number));
+        }
+        problems
+    }
+
+    fn into_model(self) -> test::QuestionModel {
+        let (answers, correct) = from_answers(self.answers);
+        let mut question = match self.kind {
+            KindDocument::Single => test::QuestionModel::new(self.text, test::QuestionKind::Single, answers, correct),
+            KindDocument::Multiple => test::QuestionModel::new(self.text, test::QuestionKind::Multiple, answers, correct),
+            KindDocument::Typed => test::QuestionModel::new(self.text, test::QuestionKind::Typed, answers, correct),
+            KindDocument::Cloze => {
+                let gaps = self.gaps.into_iter()
+                    .map(|g| {
+                        let (answers, correct) = from_answers(g.answers);
+                        test::GapModel::new(g.typed, answers, correct)
+                    })
+                    .collect();
+                test::QuestionModel::cloze(self.text, gaps)
+            },
+        };
+        question.question = question.question.trim().to_string();
+        question.explanation = self.explanation.trim().to_string();
+        question.points = self.points;
+        question.tags = test::parse_tags(&self.tags.join(","));
+        question
+    }
+}
+
+fn to_answers(answers: &[String], correct: &[usize]) -> Vec<AnswerDocument> {
+    answers.iter()
+        .enumerate()
+        .map(|(idx, text)| AnswerDocument { text: text.clone(), correct: correct.contains(&idx) })
+        .collect()
+}
+
+fn from_answers(answers: Vec<AnswerDocument>) -> (Vec<String>, Vec<usize>) {
+    let correct = answers.iter()
+        .enumerate()
+        .filter(|(_, a)| a.correct)
+        .map(|(idx, _)| idx)
+        .collect();
+    (answers.into_iter().map(|a| a.text.trim().to_string()).collect(), correct)
+}
+
+pub fn parse_json(file: &str, content: &str) -> Result<test::TestModel, Vec<Diagnostic>> {
+    match serde_json::from_str::<TestDocument>(content) {
+        Ok(document) => document.into_model(file),
+        Err(e) => Err(vec![Diagnostic::new(file, e.line(), e.column(), Problem::InvalidDocument(without_location(&e.to_string())))]),
+    }
+}
+
+pub fn parse_yaml(file: &str, content: &str) -> Result<test::TestModel, Vec<Diagnostic>> {
+    match serde_norway::from_str::<TestDocument>(content) {
+        Ok(document) => document.into_model(file),
+        Err(e) => {
+            let (line, column) = e.location().map(|l| (l.line(), l.column())).unwrap_or_default();
+            Err(vec![Diagnostic::new(file, line, column, Problem::InvalidDocument(without_location(&e.to_string())))])
+        },
+    }
+}
+
+pub fn to_json(model: &test::TestModel) -> Result<String, Box<dyn std::error::Error>> {
+    Ok(serde_json::to_string_pretty(&TestDocument::from_model(model))?)
+}
+
+pub fn to_yaml(model: &test::TestModel) -> Result<String, Box<dyn std::error::Error>> {
+    Ok(serde_norway::to_string(&TestDocument::from_model(model))?)
+}
+
+// the diagnostic shows the line and column already
+fn without_location(message: &str) -> String {
+    message.split(" at line ").next().unwrap_or(message).to_string()
+}
+
+
+#[cfg(test)]
+mod units {
+    use super::*;
+
+    const JSON: &str = r#"{
+        "schema_version": 1,
+        "title": "Irregular verbs",
+        "tags": ["Verbs"],
+        "time_limit": 600,
+        "questions": [
+            {"text": "Past of go", "answers": [{"text": "went", "correct": true}, {"text": "goed"}], "explanation": "irregular"},
+            {"text": "I {1} there", "kind": "cloze", "points": 2, "gaps": [{"typed": true, "answers": [{"text": "was", "correct": true}]}]}
+        ]
+    }"#;
+
+    #[test]
+    fn test_parse_json() {
+        let result = parse_json("verbs.json", JSON).unwrap();
+
+        assert_eq!(result.title, "Irregular verbs");
+        assert_eq!(result.tags, vec![String::from("verbs")]);
+        assert_eq!(result.time_limit, Some(600));
+        assert_eq!(result.questions[0].kind, test::QuestionKind::Single);
+        assert_eq!(result.questions[0].correct, vec![0]);
+        assert_eq!(result.questions[0].explanation, "irregular");
+        assert_eq!(result.questions[1].number, 2);
+        assert_eq!(result.questions[1].kind, test::QuestionKind::Cloze);
+        assert_eq!(result.questions[1].gaps, vec![test::GapModel::new(true, vec![String::from("was")], vec![0])]);
+        assert_eq!(result.questions[1].points, 2.0);
+    }
+
+    #[test]
+    fn test_round_trip_through_yaml() {
+        let mut model = parse_json("verbs.json", JSON).unwrap();
+        model.title = String::from("[2025-01-01] Irregular verbs");
+
+        let exported = to_yaml(&model).unwrap();
+        let result = parse_yaml("verbs.yaml", &exported).unwrap();
+
+        assert_eq!(result.title, "Irregular verbs");
+        assert_eq!(result.questions, model.questions);
+        assert_eq!(result.time_limit, model.time_limit);
+    }
+
+    #[test]
+    fn test_document_problems_reported() {
+        let syntax = parse_json("a.json", "{\n  \"title\": \"T\",\n  oops\n}").unwrap_err();
+        let schema = parse_yaml("b.yaml", "schema_version: 2\ntitle: Verbs\nquestions: []\n").unwrap_err();
+        let content = parse_yaml("c.yaml", "schema_version: 1\ntitle: Verbs\nnegative: 2\nquestions:\n  - text: Go\n    points: .nan\n    answers: [{text: went}]\n").unwrap_err();
+
+        assert_eq!((syntax[0].line, syntax[0].column), (3, 3));
+        assert_eq!(schema, vec![Diagnostic::new("b.yaml", 0, 0, Problem::UnsupportedSchema(2))]);
+        assert_eq!(content.into_iter().map(|d| d.problem).collect::<Vec<Problem>>(), vec![
+            Problem::InvalidOption(String::from("points"), String::from("NaN")),
+            Problem::TooFewQuestions(1),
+            Problem::InvalidOption(String::from("negative"), String::from("2")),
+            Problem::TooFewAnswers(1, 2),
+            Problem::NoCorrectAnswer(1),
+        ]);
+    }
+
+    #[test]
+    fn test_answers_not_fitting_kind_reported() {
+        let content = "schema_version: 1\ntitle: Verbs\nquestions:\n\
+            \x20 - text: Go\n    answers: [{text: went, correct: true}, {text: gone, correct: true}]\n\
+            \x20 - text: Be {1}\n    kind: cloze\n    answers: [{text: was, correct: true}]\n    gaps: [{typed: true, answers: [{text: was, correct: true}]}]\n\
+            \x20 - text: Do\n    kind: typed\n    answers: [{text: did, correct: true}]\n    gaps: [{answers: [{text: did, correct: true}]}]\n";
+
+        let result = parse_yaml("d.yaml", content).unwrap_err();
+
+        assert_eq!(result.into_iter().map(|d| d.problem).collect::<Vec<Problem>>(), vec![
+            Problem::TooManyCorrect(1),
+            Problem::UnexpectedAnswers(2),
+            Problem::UnexpectedGaps(3),
+        ]);
+    }
+}
@@ -1,32 +1,89 @@
 use crate::models::test;
 use crate::service::dbservice;
+use crate::service::{exchange, markdown, parser::{self, Diagnostic, Problem}, spreadsheet, testservice};
 
 use std::fs::{self};
 use std::path::{Path, PathBuf};
 use rust_i18n::t;
 use rusqlite::Connection;
 
+pub const IMPORT_DIR: &str = "./import";
+pub const EXPORT_DIR: &str = "./export";
+// a spreadsheet like 'words.csv' can have its columns named in 'words.map' next to it
+const MAPPING_EXTENSION: &str = "map";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Format {
+    Text,
+    Markdown,
+    Json,
+    Yaml,
+    Csv,
+    Tsv,
+}
+
+impl Format {
+    // the format comes from the extension, anything unknown is read as the text format
+    pub fn from_path(path: &Path) -> Self {
+        let extension = path.extension()
+            .map(|e| e.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        match extension.as_str() {
+            "md" | "markdown" => Format::Markdown,
+            "json" => Format::Json,
+            "yaml" | "yml" => Format::Yaml,
+            "csv" => Format::Csv,
+            "tsv" => Format::Tsv,
+            _ => Format::Text,
+        }
+    }
+
+    // spreadsheets are read with their mapping and can hold several tests, the other formats one
+    fn parse(&self, path: &Path, content: &str) -> Result<Vec<test::TestModel>, Vec<Diagnostic>> {
+        let file = file_name(path);
+        let single = match self {
+            Format::Text => parser::parse_test(&file, content),
+            Format::Markdown => markdown::parse_test(&file, content),
+            Format::Json => exchange::parse_json(&file, content),
+            Format::Yaml => exchange::parse_yaml(&file, content),
+            Format::Csv | Format::Tsv => {
+                let delimiter = if *self == Format::Tsv { '\t' } else { ',' };
+                let title = path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+                let mapping_path = path.with_extension(MAPPING_EXTENSION);
+                let mapping = fs::read_to_string(&mapping_path).ok();
+                let mapping_name = file_name(&mapping_path);
+                return spreadsheet::parse_table(&file, &title, content, delimiter, mapping.as_deref().map(|m| (mapping_name.as_str(), m)));
+            },
+        };
+        single.map(|model| vec![model])
+    }
+}
+
 pub fn import_test_files(locale: &str, conn: &Connection) -> Vec<String> {
     let mut logs: Vec<String> = vec![];
-    let files = read_test_files();
+    let files = match read_test_files() {
+        Ok(files) => files,
+        Err(e) => return vec![format!("{}: {:?} {}", t!("import.no_dir", locale = locale), IMPORT_DIR, e)],
+    };
     files.iter()
-        .map(|f| format!("{}: {:?}", t!("import.parsing", locale = locale), f.file_name().unwrap()))
+        .map(|f| format!("{}: {:?}", t!("import.parsing", locale = locale), file_name(f)))
         .for_each(|l| logs.push(l));
 
     let tests = files.iter()
-        .map(|f| read_file_content(f))
-        .collect::<Vec<test::TestModel>>();
+        .map(read_file_content)
+        .collect::<Vec<Result<Vec<test::TestModel>, Vec<Diagnostic>>>>();
 
     let mut valid_logs: Vec<String> = vec![];
     let mut invalid_logs: Vec<String> = vec![];
-    let mut idx = 0;
-    for test in &tests {
-        if validate_structure(&test) {
-            valid_logs.push(format!("{}: {:?}", t!("import.valid", locale = locale), files[idx].file_name().unwrap()))
-        } else {
-            invalid_logs.push(format!("{}: {:?}", t!("import.invalid", locale = locale), files[idx].file_name().unwrap()))
+    for (idx, test) in tests.iter().enumerate() {
+        match test {
+            Ok(_) => valid_logs.push(format!("{}: {:?}", t!("import.valid", locale = locale), file_name(&files[idx]))),
+            Err(diagnostics) => {
+                invalid_logs.push(format!("{}: {:?}", t!("import.invalid", locale = locale), file_name(&files[idx])));
+                diagnostics.iter()
+                    .for_each(|d| invalid_logs.push(format!("  {}", d.describe(locale))));
+            },
         }
-        idx += 1;
     }
     logs.push(String::from(" "));
     valid_logs.into_iter().for_each(|l| logs.push(l));
@@ -35,7 +92,8 @@ pub fn import_test_files(locale: &str, conn: &Connection) -> Vec<String> {
     logs.push(String::from(" "));
 
     tests.iter()
-        .filter(|t| validate_structure(t))
+        .filter_map(|t| t.as_ref().ok())
+        .flatten()
         .map(|t| save_to_db(t, locale, conn))
         .for_each(|l| logs.push(l));
     
@@ -49,31 +107,129 @@ pub fn import_test_files(locale: &str, conn: &Connection) -> Vec<String> {
         .collect::<Vec<String>>()
 }
 
-pub fn read_test_files() -> Vec<PathBuf> {
-    let imp_path = Path::new("./import");
+// the same checks as the import with a preview of every valid test, nothing is saved and no file is moved,
+// paths can be files or directories of them, returns the report and the number of invalid files
+pub fn validate_test_files(locale: &str, paths: &[PathBuf]) -> (Vec<String>, usize) {
+    let files = paths.iter()
+        .flat_map(|p| if p.is_dir() { list_files(p) } else { vec![p.clone()] })
+        .collect::<Vec<PathBuf>>();
+    if files.is_empty() {
+        return (vec![t!("import.none", locale = locale).to_string()], 0);
+    }
 
-    if !imp_path.try_exists().unwrap() {
-        match fs::create_dir(imp_path) {
-           Err(_) => println!("Could not create dir"),
-           _ => ()
+    let mut logs: Vec<String> = vec![];
+    let mut invalid = 0;
+    for file in files.iter() {
+        match read_file_content(file) {
+            Ok(models) => models.iter()
+                .for_each(|model| logs.push(format!("{}: {:?} - {}", t!("import.valid", locale = locale), file_name(file),
+                    t!("import.preview", locale = locale, title = model.title, count = model.questions.len())))),
+            Err(diagnostics) => {
+                invalid += 1;
+                logs.push(format!("{}: {:?}", t!("import.invalid", locale = locale), file_name(file)));
+                diagnostics.iter()
+                    .for_each(|d| logs.push(format!("  {}", d.describe(locale))));
+            },
         }
-        return vec![];
     }
+    logs.push(String::from(" "));
+    logs.push(t!("import.checked", locale = locale, count = files.len(), invalid = invalid).to_string());
+    (logs, invalid)
+}
+
+// every test in the database, or only the one with the given id, is written to its own file in the directory,
+// as YAML or else as JSON, tests the import would reject for having too few questions are skipped,
+// returns the report and the number of tests that could not be written
+pub fn export_test_files(locale: &str, conn: &Connection, dir: &Path, format: Format, exam: Option<&str>) -> (Vec<String>, usize) {
+    let exams = testservice::get_exams(conn).into_iter()
+        .filter(|e| exam.is_none_or(|id| e.0 == id))
+        .collect::<Vec<(String, String)>>();
+    if let Some(id) = exam && exams.is_empty() {
+        return (vec![t!("export.unknown", locale = locale, id = id).to_string()], 1);
+    }
+    if let Err(e) = fs::create_dir_all(dir) {
+        return (vec![format!("{}: {:?} {}", t!("export.failed", locale = locale), dir, e)], 1);
+    }
+    let extension = if format == Format::Yaml { "yaml" } else { "json" };
+
+    let mut logs: Vec<String> = vec![];
+    let (mut saved, mut failed) = (0, 0);
+    for (id, name) in exams.iter() {
+        let Some(model) = dbservice::get_test_by_id(conn, id.clone()) else {
+            failed += 1;
+            logs.push(format!("{}: {:?}", t!("export.failed", locale = locale), name));
+            continue;
+        };
+        if model.questions.len() < 2 {
+            logs.push(format!("{}: {:?}", t!("export.skipped", locale = locale, count = model.questions.len()), name));
+            continue;
+        }
+        let path = dir.join(format!("{}-{}.{}", id, slug(name), extension));
+        let written = match format {
+            Format::Yaml => exchange::to_yaml(&model),
+            _ => exchange::to_json(&model),
+        };
+        match written.and_then(|content| Ok(fs::write(&path, content)?)) {
+            Ok(_) => {
+                saved += 1;
+                logs.push(format!("{}: {:?}", t!("export.saved", locale = locale), file_name(&path)));
+            },
+            Err(e) => {
+                failed += 1;
+                logs.push(format!("{}: {:?} {}", t!("export.failed", locale = locale), name, e));
+            },
+        }
+    }
+    logs.push(String::from(" "));
+    logs.push(t!("export.finished", locale = locale, count = saved, dir = dir.display()).to_string());
+    (logs, failed)
+}
+
+// file name friendly version of a test name
+fn slug(name: &str) -> String {
+    name.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(|w| w.to_lowercase())
+        .collect::<Vec<String>>()
+        .join("-")
+}
+
+// the import directory is created when it is missing, the error is shown in the import report
+pub fn read_test_files() -> std::io::Result<Vec<PathBuf>> {
+    let imp_path = Path::new(IMPORT_DIR);
+
+    if !imp_path.try_exists().unwrap_or(false) {
+        fs::create_dir(imp_path)?;
+        return Ok(vec![]);
+    }
+    Ok(list_files(imp_path))
+}
 
-    let mut files = fs::read_dir(imp_path).unwrap()
-        .map(|item| item.map(|i| i.path()))
-        .filter(|p| !p.is_err())
-        .map(|p| p.unwrap())
-        .filter(|p| !p.is_dir())
+fn list_files(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return vec![];
+    };
+    let mut files = entries
+        .filter_map(|item| item.ok().map(|i| i.path()))
+        .filter(|p| !p.is_dir() && !is_mapping(p))
         .collect::<Vec<_>>();
     files.sort();
     files
 }
 
+fn is_mapping(path: &Path) -> bool {
+    path.extension().is_some_and(|e| e.eq_ignore_ascii_case(MAPPING_EXTENSION))
+}
+
+// the mapping of a spreadsheet goes along with it
 fn move_to_finished(path: PathBuf, remove: bool) { 
+    let mapping = path.with_extension(MAPPING_EXTENSION);
+    if matches!(Format::from_path(&path), Format::Csv | Format::Tsv) && mapping.exists() {
+        move_to_finished(mapping, remove);
+    }
     let mut moved = PathBuf::new();
     moved.push(r"./finished");
-    moved.push(path.file_name().unwrap());
+    moved.push(file_name(&path));
     //println!("Moving file to: {:?}", moved.clone());
     let _ = fs::copy(&path, moved);
     if remove {
@@ -81,52 +237,121 @@ fn move_to_finished(path: PathBuf, remove: bool) {
     }
 }
 
-fn read_file_content(path: &PathBuf) -> test::TestModel {
-    let message: String = fs::read_to_string(path).unwrap();
-    let split_t_q: Vec<&str> = message.split("====").collect();
-    let questions = &split_t_q[1..].iter()
-        .map(|qf| parse_question(qf))
-        .collect::<Vec<test::QuestionModel>>();
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
 
-    test::TestModel::new(String::from("0"), split_t_q[0].trim().to_string(), questions.to_vec())
+fn read_file_content(path: &PathBuf) -> Result<Vec<test::TestModel>, Vec<Diagnostic>> {
+    let name = file_name(path);
+    match fs::read_to_string(path) {
+        Ok(content) => Format::from_path(path).parse(path, &content),
+        Err(e) => Err(vec![Diagnostic::new(&name, 0, 0, Problem::Unreadable(e.to_string()))]),
+    }
 }
 
-fn parse_question(unparsed: &str) -> test::QuestionModel {
-    let splits: Vec<&str> = unparsed.split("----").collect();
-    let mut idx = 0;
-    let mut correct = 10;
-    let mut answers = vec![];
-    splits[1].split("\n")
-        .filter(|l| l.len() > 0)
-        .for_each(|q| {
-            if q.starts_with("+") {
-                correct = idx;
-            }
-            answers.push(q[1..].trim().to_string());
-            idx += 1;
-        });
-
-    test::QuestionModel::new(splits[0].trim().to_string(), answers, correct.try_into().unwrap())
+fn save_to_db(model: &test::TestModel, locale: &str, conn: &Connection) -> String {
+    match dbservice::save_new_test(conn, model) {
+        Ok(_) => format!("{}: {:?}",t!("import.save", locale = locale),  model.title),
+        Err(e) => format!("Error saving to db test: {} with error: {}", model.title, e),
+    }
 }
 
-fn validate_structure(model: &test::TestModel) -> bool {
-    if model.title.len() < 2 || model.questions.len() < 2 {
-        return false;
+
+
+#[cfg(test)]
+mod units {
+    use super::*;
+
+    #[test]
+    fn test_validate_writes_nothing() {
+        let dir = std::env::temp_dir().join(format!("ltr-validate-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir(&dir).unwrap();
+        fs::write(dir.join("good.txt"), "Verbs\n====\nGo\n----\n= went\n====\nBe\n----\n= was\n").unwrap();
+        fs::write(dir.join("bad.txt"), "Verbs\n====\nGo\n----\n- went\n- goed\n").unwrap();
+
+        let (logs, invalid) = validate_test_files("en", std::slice::from_ref(&dir));
+        let left = list_files(&dir);
+        let _ = fs::remove_dir_all(&dir);
+
+        assert_eq!(invalid, 1);
+        assert!(logs.iter().any(|l| l.ends_with("\"good.txt\" - \"Verbs\", 2 question(s)")));
+        assert!(logs.contains(&String::from("  bad.txt:3:1: question 1 has no answer marked as correct")));
+        assert_eq!(left.len(), 2);
     }
 
-    for question in &model.questions {
-        if question.question.len() < 2 || question.correct == 10 || question.answers.len() != 4 {
-            return false;
+    #[test]
+    fn test_exported_tests_import_again() {
+        let conn = testservice::init_conn_and_populate();
+        let dir = std::env::temp_dir().join(format!("ltr-export-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let mut tagged = testservice::get_test_by_id(&conn, String::from("1")).unwrap();
+        tagged.tags = vec![String::from("b1")];
+        tagged.questions[0].tags = vec![String::from("verbs")];
+        tagged.negative = Some(0.25);
+        dbservice::save_new_test(&conn, &tagged).unwrap();
+
+        // the demo data has a test with a single question, the import needs two of them
+        let exams = testservice::get_exams(&conn).into_iter()
+            .filter_map(|e| dbservice::get_test_by_id(&conn, e.0))
+            .collect::<Vec<test::TestModel>>();
+        let (logs, failed) = export_test_files("en", &conn, &dir, Format::Yaml, None);
+        let exported = list_files(&dir);
+        let imported = exported.iter()
+            .map(|p| (file_name(p), read_file_content(p).unwrap().remove(0)))
+            .collect::<Vec<(String, test::TestModel)>>();
+        let _ = fs::remove_dir_all(&dir);
+
+        let kept = exams.iter().filter(|t| t.questions.len() >= 2).count();
+        assert_eq!(failed, 0);
+        assert_eq!(logs.iter().filter(|l| l.starts_with("Skipped")).count(), exams.len() - kept);
+        assert_eq!(exported.len(), kept);
+        assert!(exported.iter().all(|p| Format::from_path(p) == Format::Yaml));
+        // files start with the id of the test, the imported tests have no ids and import date yet
+        for (name, mut model) in imported {
+            let original = exams.iter().find(|t| name.starts_with(&format!("{}-", t.id))).unwrap();
+            assert!(original.title.ends_with(&format!("] {}", model.title)));
+            model.id = original.id.clone();
+            model.title = original.title.clone();
+            model.questions.iter_mut()
+                .zip(original.questions.iter())
+                .for_each(|(q, o)| q.id = o.id.clone());
+            assert_eq!(&model, original);
         }
     }
 
-    return true;
-}
+    #[test]
+    fn test_single_exam_exported() {
+        let conn = testservice::init_conn_and_populate();
+        let dir = std::env::temp_dir().join(format!("ltr-export-one-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
 
-fn save_to_db(model: &test::TestModel, locale: &str, conn: &Connection) -> String {
-    match dbservice::save_new_test(conn, model) {
-        Ok(_) => format!("{}: {:?}",t!("import.save", locale = locale),  model.title),
-        Err(e) => format!("Error saving to db test: {} with error: {}", model.title, e),
+        let (_, failed) = export_test_files("en", &conn, &dir, Format::Json, Some("1"));
+        let exported = list_files(&dir);
+        let (unknown, missing) = export_test_files("en", &conn, &dir, Format::Json, Some("999"));
+        let _ = fs::remove_dir_all(&dir);
+
+        assert_eq!(failed, 0);
+        assert_eq!(exported.len(), 1);
+        assert!(file_name(&exported[0]).starts_with("1-"));
+        assert_eq!((unknown, missing), (vec![String::from("There is no test with id 999")], 1));
     }
-}
 
+    #[test]
+    fn test_spreadsheet_read_with_its_mapping() {
+        let dir = std::env::temp_dir().join(format!("ltr-sheet-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir(&dir).unwrap();
+        fs::write(dir.join("words.tsv"), "Lesson\tWord\tRight\tWrong\nL1\tgo\twent\tgoed\nL1\tbe\twas\tbeed\nL2\tsee\tsaw\tseed\nL2\tdo\tdid\tdoed\n").unwrap();
+        fs::write(dir.join("words.map"), "question: Word\nanswers: Right, Wrong\ntest: Lesson\n").unwrap();
+
+        let (logs, invalid) = validate_test_files("en", std::slice::from_ref(&dir));
+        let _ = fs::remove_dir_all(&dir);
+
+        assert_eq!(invalid, 0);
+        assert_eq!(logs.iter().filter(|l| l.starts_with("Valid test structure for file: \"words.tsv\"")).count(), 2);
+        assert_eq!(logs.last(), Some(&String::from("Checked 1 file(s), 0 invalid, nothing was imported")));
+    }
+}
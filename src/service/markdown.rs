@@ -0,0 +1,205 @@
+use crate::models::test;
+use super::parser::{self, Diagnostic, Line, OptionLine, Problem};
+
+const FRONT_MATTER: &str = "---";
+
+enum ListItem<'a> {
+    NotItem,
+    // a list item without a checkbox
+    Plain,
+    Task(bool, &'a str),
+    // a checkbox with something else than 'x' or a space
+    Invalid,
+}
+
+// a Markdown dialect: optional front matter with the test options, '# Title', a '## Question' heading
+// for every question with '- [x]' and '- [ ]' answers, and a '>' blockquote explaining the answer
+pub fn parse_test(file: &str, content: &str) -> Result<test::TestModel, Vec<Diagnostic>> {
+    let mut diagnostics = vec![];
+    let lines = content.lines()
+        .enumerate()
+        .map(|(idx, l)| (idx + 1, l))
+        .collect::<Vec<Line>>();
+    let Some((mut options, body)) = split_front_matter(&lines) else {
+        return Err(vec![Diagnostic::new(file, 1, 1, Problem::UnclosedFrontMatter)]);
+    };
+
+    // text before the first question other than the title is a description and is skipped
+    let mut title: Option<Line> = None;
+    let mut sections: Vec<Vec<Line>> = vec![];
+    for line in body {
+        if heading(line.1, 2).is_some() {
+            sections.push(vec![*line]);
+        } else if let Some(section) = sections.last_mut() {
+            section.push(*line);
+        } else if title.is_none() && let Some(text) = heading(line.1, 1) {
+            title = Some((line.0, text));
+        }
+    }
+
+    // the front matter can name the test when there is no '# Title'
+    let front_title = options.iter().position(|o| o.2 == "title").map(|idx| options.remove(idx));
+    let (title_line, title) = match (title, front_title) {
+        (Some((line, text)), _) => (line, text.to_string()),
+        (None, Some((line, _, _, value))) => (line, value),
+        (None, None) => (1, String::new()),
+    };
+    if title.chars().count() < 2 {
+        diagnostics.push(Diagnostic::new(file, title_line, 1, Problem::MissingTitle));
+    }
+
+    let questions = sections.iter()
+        .enumerate()
+        .map(|(idx, section)| parse_question(file, idx + 1, section, &mut diagnostics))
+        .collect();
+    parser::build_test(file, title, questions, options, diagnostics)
+}
+
+// the options between two '---' lines at the very top, nothing when they are never closed
+fn split_front_matter<'a, 'b>(lines: &'b [Line<'a>]) -> Option<(Vec<OptionLine>, &'b [Line<'a>])> {
+    if lines.first().map(|l| l.1.trim()) != Some(FRONT_MATTER) {
+        return Some((vec![], lines));
+    }
+    let end = lines[1..].iter().position(|l| l.1.trim() == FRONT_MATTER)? + 1;
+    let options = lines[1..end].iter()
+        .filter_map(|(line, l)| parser::split_option(l).map(|(column, key, value)| (*line, column, key, yaml_value(&value))))
+        .collect();
+    Some((options, &lines[end + 1..]))
+}
+
+// YAML quotes and flow lists like '[idioms, b2]' are dropped so the values read like in the text format
+fn yaml_value(value: &str) -> String {
+    let value = value.trim();
+    let unwrapped = [('"', '"'), ('\'', '\''), ('[', ']')].iter()
+        .find_map(|(open, close)| value.strip_prefix(*open).and_then(|v| v.strip_suffix(*close)));
+    unwrapped.unwrap_or(value).trim().to_string()
+}
+
+// the text of a heading of exactly the given level
+fn heading(line: &str, level: usize) -> Option<&str> {
+    let line = line.trim();
+    let text = line.strip_prefix(&"#".repeat(level))?;
+    if !text.is_empty() && !text.starts_with(' ') {
+        return None;
+    }
+    Some(text.trim())
+}
+
+fn list_item(line: &str) -> ListItem<'_> {
+    let Some(item) = line.strip_prefix(['-', '*', '+']).filter(|i| i.starts_with(' ')) else {
+        return ListItem::NotItem;
+    };
+    let item = item.trim_start();
+    let Some(checkbox) = item.strip_prefix('[') else {
+        return ListItem::Plain;
+    };
+    let mut chars = checkbox.chars();
+    match (chars.next(), chars.next()) {
+        (Some('x' | 'X'), Some(']')) => ListItem::Task(true, checkbox[2..].trim()),
+        (Some(' '), Some(']')) => ListItem::Task(false, checkbox[2..].trim()),
+        _ => ListItem::Invalid,
+    }
+}
+
+// the section starts with its '##' heading, the lines after it until the answers belong to the question text
+fn parse_question(file: &str, number: usize, section: &[Line], diagnostics: &mut Vec<Diagnostic>) -> (test::QuestionModel, Option<usize>) {
+    let line = section[0].0;
+    let mut text_lines: Vec<Line> = vec![(line, heading(section[0].1, 2).unwrap_or_default())];
+    let mut answers: Vec<String> = vec![];
+    let mut correct: Vec<usize> = vec![];
+    let mut explanation: Vec<&str> = vec![];
+    for l in section[1..].iter().filter(|l| !l.1.trim().is_empty()) {
+        let trimmed = l.1.trim();
+        if let Some(quote) = trimmed.strip_prefix('>') {
+            explanation.push(quote);
+            continue;
+        }
+        let in_answers = !answers.is_empty() || !explanation.is_empty();
+        match list_item(trimmed) {
+            ListItem::Task(checked, answer) => {
+                if checked {
+                    correct.push(answers.len());
+                }
+                answers.push(answer.to_string());
+            },
+            ListItem::Invalid => diagnostics.push(Diagnostic::new(file, l.0, parser::column(l.1), Problem::InvalidTaskItem(number))),
+            ListItem::Plain if in_answers => diagnostics.push(Diagnostic::new(file, l.0, parser::column(l.1), Problem::InvalidTaskItem(number))),
+            _ if in_answers => diagnostics.push(Diagnostic::new(file, l.0, parser::column(l.1), Problem::UnexpectedText(number))),
+            _ => text_lines.push((l.0, trimmed)),
+        }
+    }
+
+    let (text, options) = parser::parse_question_options(&text_lines);
+    let text = text.iter().map(|l| l.1).collect::<Vec<&str>>().join("\n").trim().to_string();
+    if answers.is_empty() {
+        diagnostics.push(Diagnostic::new(file, line, 1, Problem::MissingTaskList(number)));
+        return (test::QuestionModel::new(text, test::QuestionKind::Single, vec![], vec![]), None);
+    }
+    let kind = if correct.len() > 1 { test::QuestionKind::Multiple } else { test::QuestionKind::Single };
+    let mut question = test::QuestionModel::new(text, kind, answers, correct);
+    parser::apply_question_options(file, &mut question, options, diagnostics);
+    question.explanation = explanation.iter()
+        .flat_map(|e| e.split_whitespace())
+        .collect::<Vec<&str>>()
+        .join(" ");
+    (question, Some(line))
+}
+
+
+#[cfg(test)]
+mod units {
+    use super::*;
+
+    fn problems(content: &str) -> Vec<(usize, usize, Problem)> {
+        parse_test("test.md", content).unwrap_err().into_iter()
+            .map(|d| (d.line, d.column, d.problem))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_markdown_test() {
+        let content = "---\ntags: [Idioms, B2]\ntime_limit: \"10:00\"\n---\n# English idioms\n\nA short description.\n\n\
+            ## Break a leg\nmeans\npoints: 2\n\n- [ ] hurt yourself\n- [x] good luck\n\n> Said to actors\n> before a show.\n\n\
+            ## Pick the idioms\n* [X] piece of cake\n* [ ] piece of bread\n+ [x] once in a blue moon\n";
+
+        let result = parse_test("test.md", content).unwrap();
+
+        assert_eq!(result.title, "English idioms");
+        assert_eq!(result.tags, vec![String::from("idioms"), String::from("b2")]);
+        assert_eq!(result.time_limit, Some(600));
+        assert_eq!(result.questions[0].question, "Break a leg\nmeans");
+        assert_eq!(result.questions[0].points, 2.0);
+        assert_eq!(result.questions[0].correct, vec![1]);
+        assert_eq!(result.questions[0].explanation, "Said to actors before a show.");
+        assert_eq!(result.questions[1].number, 2);
+        assert_eq!(result.questions[1].kind, test::QuestionKind::Multiple);
+        assert_eq!(result.questions[1].correct, vec![0, 2]);
+    }
+
+    #[test]
+    fn test_title_from_front_matter() {
+        let result = parse_test("test.md", "---\ntitle: 'Verbs'\n---\n## Go\n- [x] went\n- [ ] goed\n## Be\n- [x] was\n- [ ] beed\n");
+
+        assert_eq!(result.unwrap().title, "Verbs");
+    }
+
+    #[test]
+    fn test_markdown_problems_reported() {
+        let result = problems("# T\n## Go\n- [x] went\n- [?] goed\nlater text\n## Be\nno answers\n");
+
+        assert_eq!(result, vec![
+            (1, 1, Problem::MissingTitle),
+            (2, 1, Problem::TooFewAnswers(1, 2)),
+            (4, 1, Problem::InvalidTaskItem(1)),
+            (5, 1, Problem::UnexpectedText(1)),
+            (6, 1, Problem::MissingTaskList(2)),
+        ]);
+    }
+
+    #[test]
+    fn test_unclosed_front_matter() {
+        let result = problems("---\ntags: verbs\n# Verbs\n");
+
+        assert_eq!(result, vec![(1, 1, Problem::UnclosedFrontMatter)]);
+    }
+}
@@ -0,0 +1,256 @@
+use rusqlite::Connection;
+
+// Each step upgrades the schema by exactly one version, the version of the
+// database is kept in `PRAGMA user_version`. Steps that have already been
+// released must never be edited, add a new one at the end instead.
+const MIGRATIONS: &[&str] = &[
+    // 1: initial schema
+    "CREATE TABLE IF NOT EXISTS exam (
+        id INTEGER PRIMARY KEY,
+        name TEXT NOT NULL,
+        date TEXT NOT NULL,
+        status TEXT NOT NULL
+    ) STRICT;
+
+    CREATE TABLE IF NOT EXISTS question (
+        id INTEGER PRIMARY KEY,
+        number INTEGER,
+        text TEXT NOT NULL,
+        a1 TEXT NOT NULL,
+        a2 TEXT NOT NULL,
+        a3 TEXT,
+        a4 TEXT,
+        correct INTEGER NOT NULL,
+        examid INTEGER REFERENCES exam(id)
+    ) STRICT;
+
+    CREATE TABLE IF NOT EXISTS result (
+        id INTEGER PRIMARY KEY,
+        examid INTEGER REFERENCES exam(id),
+        qnumber INTEGER,
+        given INTEGER,
+        time INTEGER,
+        date INTEGER
+    ) STRICT;",
+    // 2: attempts, every finished run of a test keeps its own results
+    "CREATE TABLE attempt (
+        id INTEGER PRIMARY KEY,
+        examid INTEGER REFERENCES exam(id),
+        started INTEGER NOT NULL,
+        finished INTEGER NOT NULL,
+        score INTEGER NOT NULL,
+        total_time INTEGER NOT NULL
+    ) STRICT;
+
+    ALTER TABLE result ADD COLUMN attemptid INTEGER REFERENCES attempt(id);
+
+    INSERT INTO attempt (examid, started, finished, score, total_time)
+        SELECT examid, date - SUM(time), date, 0, SUM(time) FROM result GROUP BY examid, date;
+
+    UPDATE result SET attemptid = (
+        SELECT attempt.id FROM attempt WHERE attempt.examid = result.examid AND attempt.finished = result.date
+    );
+
+    UPDATE attempt SET score = (
+        SELECT COUNT(*) FROM result JOIN question
+            ON question.examid = result.examid AND question.number = result.qnumber
+        WHERE result.attemptid = attempt.id AND result.given = question.correct
+    );",
+    // 3: answers in their own table so a question can have any number of them
+    "CREATE TABLE answer (
+        id INTEGER PRIMARY KEY,
+        questionid INTEGER NOT NULL REFERENCES question(id),
+        number INTEGER NOT NULL,
+        text TEXT NOT NULL
+    ) STRICT;
+
+    INSERT INTO answer (questionid, number, text) SELECT id, 0, a1 FROM question;
+    INSERT INTO answer (questionid, number, text) SELECT id, 1, a2 FROM question;
+    INSERT INTO answer (questionid, number, text) SELECT id, 2, a3 FROM question WHERE a3 IS NOT NULL;
+    INSERT INTO answer (questionid, number, text) SELECT id, 3, a4 FROM question WHERE a4 IS NOT NULL;
+
+    ALTER TABLE question DROP COLUMN a1;
+    ALTER TABLE question DROP COLUMN a2;
+    ALTER TABLE question DROP COLUMN a3;
+    ALTER TABLE question DROP COLUMN a4;",
+    // 4: question kinds, correct answers are flagged on the answer itself and
+    // results keep the full set of chosen answers with partial credit
+    "ALTER TABLE question ADD COLUMN kind TEXT NOT NULL DEFAULT 'SINGLE';
+    ALTER TABLE answer ADD COLUMN correct INTEGER NOT NULL DEFAULT 0;
+
+    UPDATE answer SET correct = 1 WHERE number = (
+        SELECT question.correct FROM question WHERE question.id = answer.questionid
+    );
+    ALTER TABLE question DROP COLUMN correct;
+
+    ALTER TABLE result ADD COLUMN chosen TEXT NOT NULL DEFAULT '';
+    ALTER TABLE result ADD COLUMN score REAL NOT NULL DEFAULT 0;
+
+    UPDATE result SET chosen = CAST(given AS TEXT) WHERE given IS NOT NULL AND given != 42;
+    UPDATE result SET score = 1 WHERE EXISTS (
+        SELECT 1 FROM question JOIN answer ON answer.questionid = question.id
+        WHERE question.examid = result.examid AND question.number = result.qnumber
+            AND answer.number = result.given AND answer.correct = 1
+    );
+    ALTER TABLE result DROP COLUMN given;",
+    // 5: typed answers keep the text as entered and how it was matched
+    "ALTER TABLE result ADD COLUMN typed TEXT NOT NULL DEFAULT '';
+    ALTER TABLE result ADD COLUMN matched TEXT NOT NULL DEFAULT '';",
+    // 6: cloze questions, answers belong to a numbered gap, 0 means no gap
+    "CREATE TABLE gap (
+        id INTEGER PRIMARY KEY,
+        questionid INTEGER NOT NULL,
+        number INTEGER NOT NULL,
+        typed INTEGER NOT NULL DEFAULT 0
+    ) STRICT;
+    ALTER TABLE answer ADD COLUMN gap INTEGER NOT NULL DEFAULT 0;
+
+    CREATE TABLE result_gap (
+        id INTEGER PRIMARY KEY,
+        resultid INTEGER NOT NULL,
+        number INTEGER NOT NULL,
+        chosen TEXT NOT NULL DEFAULT '',
+        typed TEXT NOT NULL DEFAULT '',
+        matched TEXT NOT NULL DEFAULT ''
+    ) STRICT;",
+    // 7: optional explanation shown when reviewing results
+    "ALTER TABLE question ADD COLUMN explanation TEXT NOT NULL DEFAULT '';",
+    // 8: per-test shuffle option, NULL follows the global setting
+    "ALTER TABLE exam ADD COLUMN shuffle INTEGER;",
+    // 9: time limits in seconds, NULL follows the global setting
+    "ALTER TABLE exam ADD COLUMN question_limit INTEGER;
+    ALTER TABLE exam ADD COLUMN time_limit INTEGER;
+    ALTER TABLE result ADD COLUMN timed_out INTEGER NOT NULL DEFAULT 0;",
+    // 10: paused attempts keep the order of questions and the time spent on the current one
+    "ALTER TABLE attempt ADD COLUMN status TEXT NOT NULL DEFAULT 'FINISHED';
+    ALTER TABLE attempt ADD COLUMN question_order TEXT NOT NULL DEFAULT '';
+    ALTER TABLE attempt ADD COLUMN question_time INTEGER NOT NULL DEFAULT 0;",
+    // 11: practice attempts are kept apart from exam attempts
+    "ALTER TABLE attempt ADD COLUMN mode TEXT NOT NULL DEFAULT 'EXAM';",
+    // 12: a retry of the wrong answers links to the attempt it was started from
+    "ALTER TABLE attempt ADD COLUMN parent INTEGER REFERENCES attempt(id);",
+    // 13: spaced repetition state of every question answered at least once
+    "CREATE TABLE review (
+        questionid INTEGER PRIMARY KEY REFERENCES question(id),
+        ease REAL NOT NULL,
+        interval INTEGER NOT NULL,
+        repetitions INTEGER NOT NULL,
+        due INTEGER NOT NULL
+    ) STRICT;",
+    // 14: question weights, negative marking, pass threshold and grade scale per test, NULL follows
+    // the global setting, finished attempts keep the points and grade they were given
+    "ALTER TABLE question ADD COLUMN points REAL NOT NULL DEFAULT 1;
+    ALTER TABLE exam ADD COLUMN negative REAL;
+    ALTER TABLE exam ADD COLUMN pass_threshold REAL;
+    ALTER TABLE exam ADD COLUMN grade_scale TEXT;
+    ALTER TABLE attempt ADD COLUMN points REAL;
+    ALTER TABLE attempt ADD COLUMN max_points REAL;
+    ALTER TABLE attempt ADD COLUMN passed INTEGER;
+    ALTER TABLE attempt ADD COLUMN grade TEXT;",
+    // 15: random sampling of questions, quotas are kept like 'verbs=5,idioms=2'
+    "ALTER TABLE exam ADD COLUMN sample_size INTEGER;
+    ALTER TABLE exam ADD COLUMN quotas TEXT NOT NULL DEFAULT '';",
+    // 16: tags of questions and of whole exams, like categories or levels
+    "CREATE TABLE question_tag (
+        questionid INTEGER NOT NULL REFERENCES question(id),
+        tag TEXT NOT NULL,
+        PRIMARY KEY (questionid, tag)
+    ) STRICT;
+
+    CREATE TABLE exam_tag (
+        examid INTEGER NOT NULL REFERENCES exam(id),
+        tag TEXT NOT NULL,
+        PRIMARY KEY (examid, tag)
+    ) STRICT;",
+    // 17: paused attempts keep the flagged questions and the one that was shown
+    "ALTER TABLE attempt ADD COLUMN flagged TEXT NOT NULL DEFAULT '';
+    ALTER TABLE attempt ADD COLUMN current_question INTEGER;",
+];
+
+pub fn latest_version() -> usize {
+    MIGRATIONS.len()
+}
+
+pub fn get_version(conn: &Connection) -> Result<usize, rusqlite::Error> {
+    conn.query_row("PRAGMA user_version", [], |r| r.get::<_, usize>(0))
+}
+
+pub fn migrate(conn: &Connection) -> Result<(), Box<dyn std::error::Error>> {
+    let current = get_version(conn)?;
+    if current > latest_version() {
+        return Err(format!(
+            "database schema version {} is newer than the supported version {}, please update the application",
+            current, latest_version()
+        ).into());
+    }
+
+    for (idx, step) in MIGRATIONS.iter().enumerate().skip(current) {
+        let tx = conn.unchecked_transaction()?;
+        tx.execute_batch(step)?;
+        tx.pragma_update(None, "user_version", idx + 1)?;
+        tx.commit()?;
+    }
+
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod units {
+    use super::*;
+
+    #[test]
+    fn test_migrate_fresh_db() {
+        let conn = Connection::open_in_memory().unwrap();
+
+        migrate(&conn).unwrap();
+
+        assert_eq!(get_version(&conn).unwrap(), latest_version());
+    }
+
+    #[test]
+    fn test_migrate_twice() {
+        let conn = Connection::open_in_memory().unwrap();
+        migrate(&conn).unwrap();
+
+        let result = migrate(&conn);
+
+        assert!(result.is_ok());
+        assert_eq!(get_version(&conn).unwrap(), latest_version());
+    }
+
+    #[test]
+    fn test_migrate_creates_attempts_for_old_results() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(MIGRATIONS[0]).unwrap();
+        conn.pragma_update(None, "user_version", 1).unwrap();
+        conn.execute_batch(
+            "INSERT INTO exam (name, date, status) VALUES ('t', '2025-01-01', 'FINISHED');
+            INSERT INTO question (number, text, a1, a2, correct, examid) VALUES (1, 'q1', 'a', 'b', 1, 1);
+            INSERT INTO question (number, text, a1, a2, correct, examid) VALUES (2, 'q2', 'a', 'b', 0, 1);
+            INSERT INTO result (examid, qnumber, given, time, date) VALUES (1, 1, 1, 10, 100);
+            INSERT INTO result (examid, qnumber, given, time, date) VALUES (1, 2, 1, 5, 100);
+            INSERT INTO result (examid, qnumber, given, time, date) VALUES (1, 1, 0, 7, 200);"
+        ).unwrap();
+
+        migrate(&conn).unwrap();
+
+        let attempts = conn.prepare("SELECT started, finished, score, total_time FROM attempt ORDER BY id").unwrap()
+            .query_map([], |r| Ok((r.get::<_, i64>(0)?, r.get::<_, i64>(1)?, r.get::<_, i64>(2)?, r.get::<_, i64>(3)?))).unwrap()
+            .map(|r| r.unwrap())
+            .collect::<Vec<_>>();
+        let orphans: i64 = conn.query_row("SELECT COUNT(*) FROM result WHERE attemptid IS NULL", [], |r| r.get(0)).unwrap();
+        assert_eq!(attempts, vec![(85, 100, 1, 15), (193, 200, 0, 7)]);
+        assert_eq!(orphans, 0);
+    }
+
+    #[test]
+    fn test_migrate_refuses_newer_db() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.pragma_update(None, "user_version", latest_version() + 1).unwrap();
+
+        let result = migrate(&conn);
+
+        assert!(result.is_err());
+    }
+}
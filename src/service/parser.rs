@@ -0,0 +1,637 @@
+use crate::models::test;
+use crate::models::scoring::GradeScale;
+
+use rust_i18n::t;
+
+const QUESTION_SEPARATOR: &str = "====";
+const ANSWER_SEPARATOR: &str = "----";
+
+// line number and its content
+pub type Line<'a> = (usize, &'a str);
+// line, column, key and value of a 'key: value' option
+pub type OptionLine = (usize, usize, String, String);
+
+// a problem found in a test file, lines and columns count from 1, line 0 means the whole file
+#[derive(Clone, Debug, PartialEq)]
+pub struct Diagnostic {
+    pub file: String,
+    pub line: usize,
+    pub column: usize,
+    pub problem: Problem,
+}
+
+impl Diagnostic {
+    pub fn new(file: &str, line: usize, column: usize, problem: Problem) -> Self {
+        Diagnostic { file: file.to_string(), line, column, problem }
+    }
+
+    // like 'verbs.txt:12:1: question 3 has no answer marked as correct'
+    pub fn describe(&self, locale: &str) -> String {
+        match self.line {
+            0 => format!("{}: {}", self.file, self.problem.message(locale)),
+            line => format!("{}:{}:{}: {}", self.file, line, self.column, self.problem.message(locale)),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Problem {
+    // the reason the file could not be read
+    Unreadable(String),
+    MissingTitle,
+    // number of questions found
+    TooFewQuestions(usize),
+    // option name and its value
+    InvalidOption(String, String),
+    // the rest point at a question by its number, gaps by their number as well
+    MissingAnswers(usize),
+    ShortQuestion(usize),
+    InvalidAnswer(usize),
+    ExtraBlock(usize),
+    // question number and the answers it needs
+    TooFewAnswers(usize, usize),
+    NoCorrectAnswer(usize),
+    AnswerOutsideGap(usize),
+    MissingGapMarker(usize, usize),
+    UnknownGapMarker(usize, usize),
+    TooFewGapAnswers(usize, usize),
+    NoCorrectGapAnswer(usize, usize),
+    // the Markdown dialect
+    UnclosedFrontMatter,
+    MissingTaskList(usize),
+    InvalidTaskItem(usize),
+    UnexpectedText(usize),
+    // JSON and YAML documents, the reason the document could not be read or the version found
+    InvalidDocument(String),
+    UnsupportedSchema(u32),
+    // a question whose answers do not fit its kind
+    TooManyCorrect(usize),
+    UnexpectedGaps(usize),
+    UnexpectedAnswers(usize),
+    // spreadsheets, the role or header name of the column
+    UnclosedQuote,
+    MissingColumn(String),
+    UnknownColumn(String),
+}
+
+impl Problem {
+    pub fn message(&self, locale: &str) -> String {
+        match self {
+            Problem::Unreadable(reason) => t!("import.error.unreadable", locale = locale, reason = reason).to_string(),
+            Problem::MissingTitle => t!("import.error.title", locale = locale).to_string(),
+            Problem::TooFewQuestions(count) => t!("import.error.questions", locale = locale, count = count).to_string(),
+            Problem::InvalidOption(key, value) => t!("import.error.option", locale = locale, key = key, value = value).to_string(),
+            Problem::MissingAnswers(q) => t!("import.error.no_answers", locale = locale, number = q).to_string(),
+            Problem::ShortQuestion(q) => t!("import.error.short_question", locale = locale, number = q).to_string(),
+            Problem::InvalidAnswer(q) => t!("import.error.answer_marker", locale = locale, number = q).to_string(),
+            Problem::ExtraBlock(q) => t!("import.error.extra_block", locale = locale, number = q).to_string(),
+            Problem::TooFewAnswers(q, min) => t!("import.error.few_answers", locale = locale, number = q, min = min).to_string(),
+            Problem::NoCorrectAnswer(q) => t!("import.error.no_correct", locale = locale, number = q).to_string(),
+            Problem::AnswerOutsideGap(q) => t!("import.error.outside_gap", locale = locale, number = q).to_string(),
+            Problem::MissingGapMarker(q, gap) => t!("import.error.missing_marker", locale = locale, number = q, gap = gap).to_string(),
+            Problem::UnknownGapMarker(q, gap) => t!("import.error.unknown_marker", locale = locale, number = q, gap = gap).to_string(),
+            Problem::TooFewGapAnswers(q, gap) => t!("import.error.few_gap_answers", locale = locale, number = q, gap = gap).to_string(),
+            Problem::NoCorrectGapAnswer(q, gap) => t!("import.error.no_gap_correct", locale = locale, number = q, gap = gap).to_string(),
+            Problem::UnclosedFrontMatter => t!("import.error.front_matter", locale = locale).to_string(),
+            Problem::MissingTaskList(q) => t!("import.error.no_task_list", locale = locale, number = q).to_string(),
+            Problem::InvalidTaskItem(q) => t!("import.error.task_item", locale = locale, number = q).to_string(),
+            Problem::UnexpectedText(q) => t!("import.error.unexpected_text", locale = locale, number = q).to_string(),
+            Problem::InvalidDocument(reason) => t!("import.error.document", locale = locale, reason = reason).to_string(),
+            Problem::UnsupportedSchema(version) => t!("import.error.schema", locale = locale, version = version).to_string(),
+            Problem::TooManyCorrect(q) => t!("import.error.many_correct", locale = locale, number = q).to_string(),
+            Problem::UnexpectedGaps(q) => t!("import.error.unexpected_gaps", locale = locale, number = q).to_string(),
+            Problem::UnexpectedAnswers(q) => t!("import.error.unexpected_answers", locale = locale, number = q).to_string(),
+            Problem::UnclosedQuote => t!("import.error.quote", locale = locale).to_string(),
+            Problem::MissingColumn(role) => t!("import.error.missing_column", locale = locale, role = role).to_string(),
+            Problem::UnknownColumn(name) => t!("import.error.unknown_column", locale = locale, name = name).to_string(),
+        }
+    }
+}
+
+// the text format: a header with the title and 'key: value' options, then questions separated by '===='
+// with their answers and an optional explanation after '----'
+pub fn parse_test(file: &str, content: &str) -> Result<test::TestModel, Vec<Diagnostic>> {
+    let mut diagnostics = vec![];
+    let lines = content.lines()
+        .enumerate()
+        .map(|(idx, l)| (idx + 1, l))
+        .collect::<Vec<Line>>();
+    let sections = split_at(&lines, QUESTION_SEPARATOR);
+
+    let questions = sections[1..].iter()
+        .enumerate()
+        .map(|(idx, section)| parse_question(file, idx + 1, section, &mut diagnostics))
+        .collect();
+    let (title, options) = parse_header(file, &sections[0], &mut diagnostics);
+    build_test(file, title, questions, options, diagnostics)
+}
+
+// the steps shared by every format, questions come with the line to report their problems at,
+// no line when they could not be parsed far enough to be checked
+pub fn build_test(file: &str, title: String, questions: Vec<(test::QuestionModel, Option<usize>)>, options: Vec<OptionLine>,
+    mut diagnostics: Vec<Diagnostic>) -> Result<test::TestModel, Vec<Diagnostic>> {
+    let mut checked = vec![];
+    for (idx, (mut question, line)) in questions.into_iter().enumerate() {
+        question.number = idx + 1;
+        if let Some(line) = line {
+            diagnostics.extend(validate_question(file, line, &question));
+        }
+        checked.push(question);
+    }
+    if checked.len() < 2 {
+        diagnostics.push(Diagnostic::new(file, 0, 0, Problem::TooFewQuestions(checked.len())));
+    }
+
+    let mut model = test::TestModel::new(String::from("0"), title, checked);
+    for (line, column, key, value) in options {
+        if !apply_option(&mut model, &key, &value) {
+            diagnostics.push(Diagnostic::new(file, line, column, Problem::InvalidOption(key, value)));
+        }
+    }
+
+    if !diagnostics.is_empty() {
+        diagnostics.sort_by_key(|d| (d.line, d.column));
+        return Err(diagnostics);
+    }
+    Ok(model)
+}
+
+// the checks every imported question has to pass, whatever format it came from
+pub fn validate_question(file: &str, line: usize, question: &test::QuestionModel) -> Vec<Diagnostic> {
+    let number = question.number;
+    let mut problems = vec![];
+    if question.question.chars().count() < 2 {
+        problems.push(Problem::ShortQuestion(number));
+    }
+    if question.kind == test::QuestionKind::Cloze {
+        problems.extend(validate_cloze(question));
+    } else {
+        let min_answers = if question.kind == test::QuestionKind::Typed { 1 } else { 2 };
+        if question.answers.len() < min_answers {
+            problems.push(Problem::TooFewAnswers(number, min_answers));
+        }
+        if question.correct.is_empty() || question.correct.iter().any(|c| *c >= question.answers.len()) {
+            problems.push(Problem::NoCorrectAnswer(number));
+        }
+    }
+    problems.into_iter()
+        .map(|p| Diagnostic::new(file, line, 1, p))
+        .collect()
+}
+
+fn validate_cloze(question: &test::QuestionModel) -> Vec<Problem> {
+    let number = question.number;
+    if question.gaps.is_empty() {
+        return vec![Problem::MissingAnswers(number)];
+    }
+    let mut problems = vec![];
+    for (idx, gap) in question.gaps.iter().enumerate() {
+        let min_answers = if gap.typed { 1 } else { 2 };
+        if !question.question.contains(&test::GapModel::marker(idx + 1)) {
+            problems.push(Problem::MissingGapMarker(number, idx + 1));
+        }
+        if gap.answers.len() < min_answers {
+            problems.push(Problem::TooFewGapAnswers(number, idx + 1));
+        }
+        if gap.correct.is_empty() {
+            problems.push(Problem::NoCorrectGapAnswer(number, idx + 1));
+        }
+    }
+    let next = question.gaps.len() + 1;
+    if question.question.contains(&test::GapModel::marker(next)) {
+        problems.push(Problem::UnknownGapMarker(number, next));
+    }
+    problems
+}
+
+// lines before the first separator, then every separator with the lines following it
+fn split_at<'a>(lines: &[Line<'a>], separator: &str) -> Vec<Vec<Line<'a>>> {
+    let mut sections = vec![vec![]];
+    for line in lines {
+        if line.1.trim().starts_with(separator) {
+            sections.push(vec![]);
+        }
+        sections.last_mut().unwrap().push(*line);
+    }
+    sections
+}
+
+// without the empty lines around
+pub fn trim_lines<'a, 'b>(lines: &'b [Line<'a>]) -> &'b [Line<'a>] {
+    let start = lines.iter().position(|l| !l.1.trim().is_empty()).unwrap_or(lines.len());
+    let end = lines.iter().rposition(|l| !l.1.trim().is_empty()).map(|e| e + 1).unwrap_or(start);
+    &lines[start..end.max(start)]
+}
+
+pub fn column(line: &str) -> usize {
+    line.chars().take_while(|c| c.is_whitespace()).count() + 1
+}
+
+// the value of a 'key: value' line with its column
+pub fn split_option(line: &str) -> Option<(usize, String, String)> {
+    let (key, value) = line.split_once(':')?;
+    let column = key.chars().count() + 2 + value.chars().take_while(|c| c.is_whitespace()).count();
+    Some((column, key.trim().to_lowercase(), value.trim().to_string()))
+}
+
+// the first line is the title, the following 'key: value' lines are test options
+fn parse_header(file: &str, lines: &[Line], diagnostics: &mut Vec<Diagnostic>) -> (String, Vec<OptionLine>) {
+    let lines = trim_lines(lines);
+    let title = lines.first().map(|l| l.1.trim().to_string()).unwrap_or_default();
+    if title.chars().count() < 2 {
+        let line = lines.first().map(|l| l.0).unwrap_or(1);
+        diagnostics.push(Diagnostic::new(file, line, 1, Problem::MissingTitle));
+    }
+    let options = lines.iter()
+        .skip(1)
+        .filter_map(|(line, l)| split_option(l).map(|(column, key, value)| (*line, column, key, value)))
+        .collect();
+    (title, options)
+}
+
+// false when a known option has a value that does not make sense, unknown options are skipped
+fn apply_option(model: &mut test::TestModel, key: &str, value: &str) -> bool {
+    match key {
+        "shuffle" => model.shuffle = parse_flag(value),
+        "question_limit" => model.question_limit = parse_duration(value),
+        "time_limit" => model.time_limit = parse_duration(value),
+        // a share of the points of the question and a percent
+        "negative" => model.negative = value.parse::<f64>().ok().filter(|n| (0.0..=1.0).contains(n)),
+        "pass" => model.pass_threshold = value.trim_end_matches('%').trim().parse::<f64>().ok().filter(|p| (0.0..=100.0).contains(p)),
+        // an unknown scale would silently mean no grades
+        "grades" => {
+            model.grade_scale = Some(value.to_string());
+            return value.is_empty() || GradeScale::from_code(value) != GradeScale::None;
+        },
+        "sample" => model.sample_size = value.parse::<usize>().ok().filter(|s| *s > 0),
+        "quota" => {
+            model.quotas = test::parse_quotas(value);
+            return !model.quotas.is_empty();
+        },
+        "tags" => model.tags = test::parse_tags(value),
+        _ => return true,
+    }
+    match key {
+        "shuffle" => model.shuffle.is_some(),
+        "question_limit" => model.question_limit.is_some(),
+        "time_limit" => model.time_limit.is_some(),
+        "negative" => model.negative.is_some(),
+        "pass" => model.pass_threshold.is_some(),
+        "sample" => model.sample_size.is_some(),
+        _ => true,
+    }
+}
+
+// seconds or minutes and seconds like '1:30'
+fn parse_duration(value: &str) -> Option<u64> {
+    match value.split_once(':') {
+        Some((mins, secs)) => Some(mins.trim().parse::<u64>().ok()? * 60 + secs.trim().parse::<u64>().ok()?),
+        None => value.parse::<u64>().ok(),
+    }
+}
+
+fn parse_flag(value: &str) -> Option<bool> {
+    match value.to_lowercase().as_str() {
+        "yes" | "true" | "on" => Some(true),
+        "no" | "false" | "off" => Some(false),
+        _ => None,
+    }
+}
+
+// the section starts with its '====' line, an optional third block after the answers explains the correct answer,
+// returns the question with the line it starts at, no line when there is nothing more to check
+fn parse_question(file: &str, number: usize, section: &[Line], diagnostics: &mut Vec<Diagnostic>) -> (test::QuestionModel, Option<usize>) {
+    let blocks = split_at(&section[1..], ANSWER_SEPARATOR);
+    let (text, options) = parse_question_options(trim_lines(&blocks[0]));
+    let line = text.first().map(|l| l.0).unwrap_or(section[0].0);
+    let text = text.iter().map(|l| l.1).collect::<Vec<&str>>().join("\n").trim().to_string();
+
+    let Some(answers) = blocks.get(1) else {
+        diagnostics.push(Diagnostic::new(file, line, 1, Problem::MissingAnswers(number)));
+        return (test::QuestionModel::new(text, test::QuestionKind::Single, vec![], vec![]), None);
+    };
+    let answers = &answers[1..];
+    let mut question = if answers.iter().any(|l| is_gap_header(l.1)) {
+        parse_cloze(file, number, text, answers, diagnostics)
+    } else {
+        parse_answers(file, number, text, answers, diagnostics)
+    };
+    apply_question_options(file, &mut question, options, diagnostics);
+    if let Some(explanation) = blocks.get(2) {
+        question.explanation = explanation[1..].iter()
+            .flat_map(|l| l.1.split_whitespace())
+            .collect::<Vec<&str>>()
+            .join(" ");
+    }
+    // a question has at most its text, answers and explanation, more would be lost without a word
+    for block in blocks.iter().skip(3) {
+        diagnostics.push(Diagnostic::new(file, block[0].0, 1, Problem::ExtraBlock(number)));
+    }
+    (question, Some(line))
+}
+
+pub fn apply_question_options(file: &str, question: &mut test::QuestionModel, options: Vec<OptionLine>, diagnostics: &mut Vec<Diagnostic>) {
+    for (line, column, key, value) in options {
+        match key.as_str() {
+            "points" => match value.parse::<f64>() {
+                Ok(points) if valid_points(points) => question.points = points,
+                _ => diagnostics.push(Diagnostic::new(file, line, column, Problem::InvalidOption(key, value))),
+            },
+            "tags" => question.tags = test::parse_tags(&value),
+            _ => {}
+        }
+    }
+}
+
+// a question weighs something, zero or less would take it out of the total or turn a right answer into lost points
+pub fn valid_points(points: f64) -> bool {
+    points.is_finite() && points > 0.0
+}
+
+// the last lines of the question text can set its weight and tags, like 'points: 2' or 'tags: verbs'
+pub fn parse_question_options<'a, 'b>(lines: &'b [Line<'a>]) -> (&'b [Line<'a>], Vec<OptionLine>) {
+    let mut text = lines;
+    let mut options = vec![];
+    while text.len() > 1 {
+        let (line, last) = text[text.len() - 1];
+        let option = split_option(last).filter(|(_, key, _)| ["points", "tags"].contains(&key.as_str()));
+        let Some((column, key, value)) = option else {
+            break;
+        };
+        options.push((line, column, key, value));
+        text = trim_lines(&text[..text.len() - 1]);
+    }
+    (text, options)
+}
+
+// the marker of an answer line with the answer itself, '+' correct, '-' wrong and '=' typed
+fn split_answer<'a>(file: &str, number: usize, line: &Line<'a>, diagnostics: &mut Vec<Diagnostic>) -> Option<(char, &'a str)> {
+    let trimmed = line.1.trim();
+    match trimmed.chars().next() {
+        Some(marker @ ('+' | '-' | '=')) => Some((marker, trimmed[1..].trim())),
+        _ => {
+            diagnostics.push(Diagnostic::new(file, line.0, column(line.1), Problem::InvalidAnswer(number)));
+            None
+        },
+    }
+}
+
+fn parse_answers(file: &str, number: usize, text: String, lines: &[Line], diagnostics: &mut Vec<Diagnostic>) -> test::QuestionModel {
+    let mut correct = vec![];
+    let mut answers = vec![];
+    let mut typed = false;
+    for line in lines.iter().filter(|l| !l.1.trim().is_empty()) {
+        let Some((marker, answer)) = split_answer(file, number, line, diagnostics) else {
+            continue;
+        };
+        if marker != '-' {
+            correct.push(answers.len());
+        }
+        typed |= marker == '=';
+        answers.push(answer.to_string());
+    }
+    // answers marked with '=' are accepted spellings of a typed answer,
+    // more than one answer marked with '+' makes it a 'select all that apply' question
+    let kind = if typed {
+        test::QuestionKind::Typed
+    } else if correct.len() > 1 {
+        test::QuestionKind::Multiple
+    } else {
+        test::QuestionKind::Single
+    };
+
+    test::QuestionModel::new(text, kind, answers, correct)
+}
+
+// a line like '{1}' starts the answers of a gap in a cloze question
+fn is_gap_header(line: &str) -> bool {
+    let line = line.trim();
+    line.len() > 2 && line.starts_with('{') && line.ends_with('}')
+        && line[1..line.len() - 1].parse::<usize>().is_ok()
+}
+
+// every gap gets its own block of answers, gaps with '=' answers are typed, the others are dropdowns
+fn parse_cloze(file: &str, number: usize, text: String, lines: &[Line], diagnostics: &mut Vec<Diagnostic>) -> test::QuestionModel {
+    let mut gaps: Vec<test::GapModel> = vec![];
+    for line in lines.iter().filter(|l| !l.1.trim().is_empty()) {
+        if is_gap_header(line.1) {
+            gaps.push(test::GapModel::new(false, vec![], vec![]));
+            continue;
+        }
+        let Some(gap) = gaps.last_mut() else {
+            diagnostics.push(Diagnostic::new(file, line.0, column(line.1), Problem::AnswerOutsideGap(number)));
+            continue;
+        };
+        let Some((marker, answer)) = split_answer(file, number, line, diagnostics) else {
+            continue;
+        };
+        if marker != '-' {
+            gap.correct.push(gap.answers.len());
+        }
+        gap.typed |= marker == '=';
+        gap.answers.push(answer.to_string());
+    }
+
+    test::QuestionModel::cloze(text, gaps)
+}
+
+
+#[cfg(test)]
+mod units {
+    use super::*;
+
+    fn parse_valid(content: &str) -> test::TestModel {
+        parse_test("test.txt", content).unwrap()
+    }
+
+    // a single question, the diagnostics of its syntax are dropped
+    fn parse_one(content: &str) -> test::QuestionModel {
+        let content = format!("====\n{}", content);
+        let lines = content.lines().enumerate().map(|(idx, l)| (idx + 1, l)).collect::<Vec<Line>>();
+        let (mut question, _) = parse_question("test.txt", 1, &lines, &mut vec![]);
+        question.number = 1;
+        question
+    }
+
+    fn problems(content: &str) -> Vec<(usize, usize, Problem)> {
+        parse_test("test.txt", content).unwrap_err().into_iter()
+            .map(|d| (d.line, d.column, d.problem))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_header_options() {
+        let result = parse_valid("Irregular verbs\nshuffle: yes\n====\nLess is more\n----\n+ True\n- False\n====\nGo\n----\n= went\n");
+
+        assert_eq!(result.title, "Irregular verbs");
+        assert_eq!(result.shuffle, Some(true));
+        assert_eq!(result.questions[1].number, 2);
+    }
+
+    #[test]
+    fn test_parse_time_limits() {
+        let result = parse_valid("Timed\nquestion_limit: 30\ntime_limit: 10:00\n====\nGo\n----\n= went\n====\nBe\n----\n= was\n");
+
+        assert_eq!(result.question_limit, Some(30));
+        assert_eq!(result.time_limit, Some(600));
+        assert_eq!(parse_duration("soon"), None);
+    }
+
+    #[test]
+    fn test_parse_scoring() {
+        let result = parse_valid("Graded\nnegative: 0.25\npass: 60%\ngrades: polish\n====\nGo\npoints: 2\n----\n= went\n====\nBe\n----\n= was\n");
+
+        assert_eq!(result.negative, Some(0.25));
+        assert_eq!(result.pass_threshold, Some(60.0));
+        assert_eq!(result.grade_scale, Some(String::from("polish")));
+        assert_eq!((result.questions[0].question.as_str(), result.questions[0].points), ("Go", 2.0));
+        assert_eq!(result.questions[1].points, 1.0);
+    }
+
+    #[test]
+    fn test_parse_sampling() {
+        let result = parse_valid("Pool\nsample: 1\nquota: verbs=1\ntags: Idioms, B2\n====\nGo\ntags: Verbs, B1\npoints: 2\n----\n= went\n====\nNote: be\n----\n= was\n");
+
+        assert_eq!(result.sample_size, Some(1));
+        assert_eq!(result.quotas, vec![(String::from("verbs"), 1)]);
+        assert_eq!(result.tags, vec![String::from("idioms"), String::from("b2")]);
+        assert_eq!(result.questions[0].question, "Go");
+        assert_eq!(result.questions[0].tags, vec![String::from("verbs"), String::from("b1")]);
+        assert_eq!(result.questions[0].points, 2.0);
+        assert_eq!(result.questions[1].question, "Note: be");
+    }
+
+    #[test]
+    fn test_parse_true_false() {
+        let result = parse_one("Less is more\n----\n+ True\n- False\n");
+
+        assert_eq!(result.answers, vec![String::from("True"), String::from("False")]);
+        assert_eq!(result.correct, vec![0]);
+        assert_eq!(result.kind, test::QuestionKind::Single);
+    }
+
+    #[test]
+    fn test_parse_explanation() {
+        let result = parse_one("Stop ... at the world\n----\n- seeing\n+ looking\n----\nWe look at things,\nwe see what is in front of us.\n");
+
+        assert_eq!(result.answers.len(), 2);
+        assert_eq!(result.explanation, "We look at things, we see what is in front of us.");
+    }
+
+    #[test]
+    fn test_parse_select_all() {
+        let result = parse_one("Choose all correct forms\n----\n+ I was\n- I were\n+ I had been\n");
+
+        assert_eq!(result.correct, vec![0, 2]);
+        assert_eq!(result.kind, test::QuestionKind::Multiple);
+    }
+
+    #[test]
+    fn test_parse_typed() {
+        let result = parse_one("Past simple of 'write'\n----\n= wrote\n= writ\n");
+
+        assert_eq!(result.kind, test::QuestionKind::Typed);
+        assert_eq!(result.answers, vec![String::from("wrote"), String::from("writ")]);
+        assert_eq!(result.correct, vec![0, 1]);
+    }
+
+    #[test]
+    fn test_parse_cloze() {
+        let result = parse_one("I wish you {1} so {2}.\n----\n{1}\n- was\n+ were\n{2}\n= dumb\n= dumm\n");
+
+        assert_eq!(result.kind, test::QuestionKind::Cloze);
+        assert_eq!(result.gaps.len(), 2);
+        assert!(!result.gaps[0].typed);
+        assert_eq!(result.gaps[0].correct, vec![1]);
+        assert!(result.gaps[1].typed);
+        assert_eq!(result.gaps[1].answers, vec![String::from("dumb"), String::from("dumm")]);
+    }
+
+    #[test]
+    fn test_validate_cloze_gap_count() {
+        let missing = parse_one("I wish you {1} so {2}.\n----\n{1}\n- was\n+ were\n");
+        let complete = parse_one("I wish you {1} so dumb.\n----\n{1}\n- was\n+ were\n");
+
+        assert_eq!(validate_question("test.txt", 2, &missing).into_iter().map(|d| d.problem).collect::<Vec<Problem>>(),
+            vec![Problem::UnknownGapMarker(1, 2)]);
+        assert!(validate_question("test.txt", 2, &complete).is_empty());
+    }
+
+    #[test]
+    fn test_validate_typed_single_accepted() {
+        let result = parse_test("test.txt", "Mixed\n====\nPast simple of 'go'\n----\n= went\n====\nLess is more\n----\n- True\n+ False\n");
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_validate_any_answer_count() {
+        let result = parse_test("test.txt", "Mixed\n====\nLess is more\n----\n- True\n+ False\n====\nPick the fifth\n----\n- a\n- b\n- c\n- d\n+ e\n");
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_validate_single_answer() {
+        let result = problems("Mixed\n====\nOnly one\n----\n+ yes\n====\nLess is more\n----\n- True\n+ False\n");
+
+        assert_eq!(result, vec![(3, 1, Problem::TooFewAnswers(1, 2))]);
+    }
+
+    #[test]
+    fn test_validate_no_correct_answer() {
+        let result = problems("Mixed\n====\nLess is more\n----\n- True\n+ False\n====\nNothing marked\n----\n- a\n- b\n");
+
+        assert_eq!(result, vec![(8, 1, Problem::NoCorrectAnswer(2))]);
+    }
+
+    #[test]
+    fn test_scoring_out_of_range() {
+        let result = problems("Graded\nnegative: 1.5\npass: 120%\n====\nGo\n----\n= went\n====\nBe\n----\n= was\n");
+
+        assert_eq!(result, vec![
+            (2, 11, Problem::InvalidOption(String::from("negative"), String::from("1.5"))),
+            (3, 7, Problem::InvalidOption(String::from("pass"), String::from("120%"))),
+        ]);
+    }
+
+    #[test]
+    fn test_points_and_grades_checked() {
+        let result = problems("Graded\ngrades: stars\n====\nGo\npoints: -2\n----\n= went\n====\nBe\npoints: nan\n----\n= was\n====\nDo\npoints: 0\n----\n= did\n");
+
+        assert_eq!(result, vec![
+            (2, 9, Problem::InvalidOption(String::from("grades"), String::from("stars"))),
+            (5, 9, Problem::InvalidOption(String::from("points"), String::from("-2"))),
+            (10, 9, Problem::InvalidOption(String::from("points"), String::from("nan"))),
+            (15, 9, Problem::InvalidOption(String::from("points"), String::from("0"))),
+        ]);
+    }
+
+    #[test]
+    fn test_extra_block_reported() {
+        let result = problems("Split\n====\nGo\n----\n= went\n----\nIrregular\n----\nverb\n====\nBe\n----\n= was\n---- not a separator\n");
+
+        assert_eq!(result, vec![(8, 1, Problem::ExtraBlock(1))]);
+    }
+
+    #[test]
+    fn test_bad_lines_reported_without_panic() {
+        let result = problems("T\nshuffle: maybe\n====\nŻółw\n----\n  ą wrong marker\n+ right\n- wrong\n====\n");
+
+        assert_eq!(result, vec![
+            (1, 1, Problem::MissingTitle),
+            (2, 10, Problem::InvalidOption(String::from("shuffle"), String::from("maybe"))),
+            (6, 3, Problem::InvalidAnswer(1)),
+            (9, 1, Problem::MissingAnswers(2)),
+        ]);
+    }
+
+    #[test]
+    fn test_describe_diagnostic() {
+        let diagnostic = Diagnostic::new("verbs.txt", 12, 1, Problem::NoCorrectAnswer(3));
+        let whole_file = Diagnostic::new("verbs.txt", 0, 0, Problem::TooFewQuestions(1));
+
+        assert_eq!(diagnostic.describe("en"), "verbs.txt:12:1: question 3 has no answer marked as correct");
+        assert_eq!(whole_file.describe("en"), "verbs.txt: the test has 1 question(s), at least 2 are needed");
+    }
+}
@@ -0,0 +1,300 @@
+use crate::models::test;
+use super::parser::{self, Diagnostic, Problem};
+
+use std::mem;
+
+// every value of a row with the line the row starts at
+type Record = (usize, Vec<String>);
+// name of a test and its questions with the line of their row
+type Rows = (String, Vec<(test::QuestionModel, Option<usize>)>);
+
+// where the parts of a question are in a row, several columns can hold answers
+#[derive(Debug, Default, PartialEq)]
+struct Columns {
+    question: Option<usize>,
+    answers: Vec<usize>,
+    correct: Option<usize>,
+    explanation: Option<usize>,
+    tags: Option<usize>,
+    // rows with the same value go to the same test
+    test: Option<usize>,
+}
+
+impl Columns {
+    // false when the role is not known
+    fn assign(&mut self, role: &str, idx: usize) -> bool {
+        match role {
+            "question" => self.question = Some(idx),
+            "answer" | "answers" => self.answers.push(idx),
+            "correct" => self.correct = Some(idx),
+            "explanation" => self.explanation = Some(idx),
+            "tag" | "tags" => self.tags = Some(idx),
+            "test" => self.test = Some(idx),
+            _ => return false,
+        }
+        true
+    }
+}
+
+// a question bank kept in a spreadsheet, the first row names the columns, a row is a question:
+// - question, answers and explanation hold text, answers can be split into several columns
+//   like 'answer 1', 'answer 2' or separated with '|' in one of them
+// - correct holds the numbers of the correct answers counted from 1 or their text separated with '|',
+//   text that is not among the answers is added to them, when any answer of the row is a number
+//   the values are only ever read as text, the first answer is correct only when there is no correct column
+// - tags holds the tags of the question, rows with the same test value make a test named after it
+// the mapping is the name and content of a sidecar file with 'role: header' lines, like 'question: Word',
+// used instead of the header names, 'answers' can list several headers and 'title' names the test
+pub fn parse_table(file: &str, title: &str, content: &str, delimiter: char, mapping: Option<(&str, &str)>) -> Result<Vec<test::TestModel>, Vec<Diagnostic>> {
+    let records = match split_records(content, delimiter) {
+        Ok(records) => records,
+        Err(line) => return Err(vec![Diagnostic::new(file, line, 1, Problem::UnclosedQuote)]),
+    };
+    let Some((header, rows)) = records.split_first() else {
+        return Err(vec![Diagnostic::new(file, 0, 0, Problem::TooFewQuestions(0))]);
+    };
+
+    let mut diagnostics = vec![];
+    let mut title = title.to_string();
+    let columns = match mapping {
+        Some((map_file, map_content)) => map_columns(&header.1, map_file, map_content, &mut title, &mut diagnostics),
+        None => header_columns(&header.1),
+    };
+    if columns.question.is_none() {
+        diagnostics.push(Diagnostic::new(file, header.0, 1, Problem::MissingColumn(String::from("question"))));
+    }
+    if columns.answers.is_empty() && columns.correct.is_none() {
+        diagnostics.push(Diagnostic::new(file, header.0, 1, Problem::MissingColumn(String::from("answers"))));
+    }
+    if !diagnostics.is_empty() {
+        return Err(diagnostics);
+    }
+
+    // tests in the order their first row comes in, rows without a test value go to the one named after the file
+    let mut tests: Vec<Rows> = vec![];
+    for (line, fields) in rows {
+        let name = cell(fields, columns.test);
+        let name = if name.is_empty() { title.clone() } else { name.to_string() };
+        let question = (parse_row(fields, &columns), Some(*line));
+        match tests.iter_mut().find(|t| t.0 == name) {
+            Some(test) => test.1.push(question),
+            None => tests.push((name, vec![question])),
+        }
+    }
+    if tests.is_empty() {
+        return Err(vec![Diagnostic::new(file, 0, 0, Problem::TooFewQuestions(0))]);
+    }
+
+    // problems of a test from a 'test' column name it next to the file
+    let mut models = vec![];
+    for (name, questions) in tests {
+        let label = if columns.test.is_some() { format!("{} ({})", file, name) } else { file.to_string() };
+        let mut found = vec![];
+        if name.chars().count() < 2 {
+            found.push(Diagnostic::new(&label, 0, 0, Problem::MissingTitle));
+        }
+        match parser::build_test(&label, name, questions, vec![], found) {
+            Ok(model) => models.push(model),
+            Err(found) => diagnostics.extend(found),
+        }
+    }
+    if !diagnostics.is_empty() {
+        return Err(diagnostics);
+    }
+    Ok(models)
+}
+
+// quoted values can hold the delimiter, line breaks and doubled quotes, empty rows are skipped,
+// the line of a quote that is never closed when there is one
+fn split_records(content: &str, delimiter: char) -> Result<Vec<Record>, usize> {
+    let mut records: Vec<Record> = vec![];
+    let mut fields: Vec<String> = vec![];
+    let mut field = String::new();
+    let (mut line, mut start, mut quote_line) = (1, 1, 0);
+    let mut quoted = false;
+    let mut chars = content.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            },
+            '"' if quoted => quoted = false,
+            '"' if field.trim().is_empty() => {
+                field.clear();
+                quoted = true;
+                quote_line = line;
+            },
+            '\r' => {},
+            '\n' if quoted => {
+                field.push('\n');
+                line += 1;
+            },
+            '\n' => {
+                fields.push(mem::take(&mut field));
+                records.push((start, mem::take(&mut fields)));
+                line += 1;
+                start = line;
+            },
+            c if c == delimiter && !quoted => fields.push(mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    if quoted {
+        return Err(quote_line);
+    }
+    if !field.is_empty() || !fields.is_empty() {
+        fields.push(field);
+        records.push((start, fields));
+    }
+    records.retain(|r| r.1.iter().any(|f| !f.trim().is_empty()));
+    Ok(records)
+}
+
+// headers like 'Question', 'Answer 1', 'Answer 2' or 'Tags', the others are skipped
+fn header_columns(header: &[String]) -> Columns {
+    let mut columns = Columns::default();
+    for (idx, name) in header.iter().enumerate() {
+        let name = name.trim().to_lowercase();
+        let role = if name.starts_with("answer") { "answers" } else { name.as_str() };
+        columns.assign(role, idx);
+    }
+    columns
+}
+
+fn map_columns(header: &[String], map_file: &str, map_content: &str, title: &mut String, diagnostics: &mut Vec<Diagnostic>) -> Columns {
+    let mut columns = Columns::default();
+    let options = map_content.lines()
+        .enumerate()
+        .filter_map(|(idx, l)| parser::split_option(l).map(|(column, key, value)| (idx + 1, column, key, value)));
+    for (line, column, key, value) in options {
+        if key == "title" {
+            *title = value;
+            continue;
+        }
+        for name in value.split(',').map(|n| n.trim()).filter(|n| !n.is_empty()) {
+            match header.iter().position(|h| h.trim().eq_ignore_ascii_case(name)) {
+                Some(idx) if columns.assign(&key, idx) => {},
+                Some(_) => diagnostics.push(Diagnostic::new(map_file, line, 1, Problem::InvalidOption(key.clone(), value.clone()))),
+                None => diagnostics.push(Diagnostic::new(map_file, line, column, Problem::UnknownColumn(name.to_string()))),
+            }
+        }
+    }
+    columns
+}
+
+fn cell(fields: &[String], idx: Option<usize>) -> &str {
+    idx.and_then(|i| fields.get(i))
+        .map(|f| f.trim())
+        .unwrap_or_default()
+}
+
+// one answer makes a typed question, more than one correct answer a multiple choice one
+fn parse_row(fields: &[String], columns: &Columns) -> test::QuestionModel {
+    let mut answers = columns.answers.iter()
+        .flat_map(|idx| cell(fields, Some(*idx)).split('|'))
+        .map(|a| a.trim().to_string())
+        .filter(|a| !a.is_empty())
+        .collect::<Vec<String>>();
+    let mut correct = vec![];
+    if columns.correct.is_none() && !answers.is_empty() {
+        correct.push(0);
+    }
+    // '2' could be the second answer or an answer reading '2', numeric answers settle it as text
+    let by_number = !answers.iter().any(|a| a.parse::<f64>().is_ok());
+    for value in cell(fields, columns.correct).split('|').map(|c| c.trim()).filter(|c| !c.is_empty()) {
+        if let Some(idx) = answers.iter().position(|a| a == value) {
+            correct.push(idx);
+        } else if by_number && let Ok(number) = value.parse::<usize>() {
+            // a number outside of the answers is reported by the validation
+            correct.push(number.checked_sub(1).unwrap_or(usize::MAX));
+        } else {
+            answers.push(value.to_string());
+            correct.push(answers.len() - 1);
+        }
+    }
+    correct.sort();
+    correct.dedup();
+
+    let kind = match (answers.len(), correct.len()) {
+        (1, _) => test::QuestionKind::Typed,
+        (_, c) if c > 1 => test::QuestionKind::Multiple,
+        _ => test::QuestionKind::Single,
+    };
+    let mut question = test::QuestionModel::new(cell(fields, columns.question).to_string(), kind, answers, correct);
+    question.explanation = cell(fields, columns.explanation).split_whitespace().collect::<Vec<&str>>().join(" ");
+    question.tags = test::parse_tags(cell(fields, columns.tags));
+    question
+}
+
+
+#[cfg(test)]
+mod units {
+    use super::*;
+
+    #[test]
+    fn test_split_quoted_records() {
+        let result = split_records("a,\"b, \"\"c\"\"\"\r\n\n\"multi\nline\",d\n", ',');
+
+        assert_eq!(result, Ok(vec![
+            (1, vec![String::from("a"), String::from("b, \"c\"")]),
+            (3, vec![String::from("multi\nline"), String::from("d")]),
+        ]));
+        assert_eq!(split_records("a\n\"b,c\n", ','), Err(2));
+    }
+
+    #[test]
+    fn test_parse_table_from_header() {
+        let content = "Question\tAnswer 1\tAnswer 2\tCorrect\tTags\n\
+            Past of go\twent\tgoed\t1\tverbs\n\
+            Pick the vowels\ta|b|e\t\ta|e\t\n\
+            Dog in Polish\t\t\tpies\t\n\
+            Half of 4\t2|4\t\t1\t\n";
+
+        let result = parse_table("words.tsv", "words", content, '\t', None).unwrap();
+
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].title, "words");
+        assert_eq!(result[0].questions[0].correct, vec![0]);
+        assert_eq!(result[0].questions[0].tags, vec![String::from("verbs")]);
+        assert_eq!(result[0].questions[1].kind, test::QuestionKind::Multiple);
+        assert_eq!(result[0].questions[1].correct, vec![0, 2]);
+        assert_eq!(result[0].questions[2].kind, test::QuestionKind::Typed);
+        assert_eq!(result[0].questions[2].answers, vec![String::from("pies")]);
+        // with numbers among the answers a number is an answer, not a position
+        assert_eq!(result[0].questions[3].answers, vec![String::from("2"), String::from("4"), String::from("1")]);
+        assert_eq!(result[0].questions[3].correct, vec![2]);
+    }
+
+    #[test]
+    fn test_parse_table_with_mapping_and_tests() {
+        let content = "Lesson,Word,Right,Wrong,Note\nL1,go,went,goed,irregular\nL1,be,was,beed,\nL2,see,saw,seed,\nL2,do,did,doed,\n";
+        let mapping = "question: Word\nanswers: Right, Wrong\nexplanation: Note\ntest: Lesson\n";
+
+        let result = parse_table("verbs.csv", "verbs", content, ',', Some(("verbs.map", mapping))).unwrap();
+
+        assert_eq!(result.iter().map(|t| t.title.as_str()).collect::<Vec<&str>>(), vec!["L1", "L2"]);
+        assert_eq!(result[0].questions[0].answers, vec![String::from("went"), String::from("goed")]);
+        assert_eq!(result[0].questions[0].correct, vec![0]);
+        assert_eq!(result[0].questions[0].explanation, "irregular");
+        assert_eq!(result[1].questions[1].number, 2);
+    }
+
+    #[test]
+    fn test_table_problems_reported() {
+        let unknown = parse_table("a.csv", "a", "Word,Right\ngo,went\n", ',', Some(("a.map", "question: Word\nanswers: Wrong\n")));
+        let rows = parse_table("b.csv", "b", "test,question,answer,correct\nT1,go,went,3\nT1,be,was,\nT2,do,did,\n", ',', None);
+
+        assert_eq!(unknown, Err(vec![
+            Diagnostic::new("a.map", 2, 10, Problem::UnknownColumn(String::from("Wrong"))),
+            Diagnostic::new("a.csv", 1, 1, Problem::MissingColumn(String::from("answers"))),
+        ]));
+        // an empty correct cell is not taken for the first answer
+        assert_eq!(rows, Err(vec![
+            Diagnostic::new("b.csv (T1)", 2, 1, Problem::NoCorrectAnswer(1)),
+            Diagnostic::new("b.csv (T1)", 3, 1, Problem::NoCorrectAnswer(2)),
+            Diagnostic::new("b.csv (T2)", 0, 0, Problem::TooFewQuestions(1)),
+            Diagnostic::new("b.csv (T2)", 4, 1, Problem::NoCorrectAnswer(1)),
+        ]));
+    }
+}
@@ -1,35 +1,34 @@
 use super::dbservice;
-use crate::models::test;
+use crate::models::{builder, schedule, stats, test};
 use rusqlite::Connection;
+use std::fs;
+use std::path::Path;
 
+// in-memory database with mock data, used for the demo mode and tests
 pub fn init_conn_and_populate() -> Connection {
-    // TODO move to file 
-    // remove current dev db and recreate with mock data
     let conn = Connection::open_in_memory().unwrap();
     let _ = dbservice::create_schema(&conn);
-    let _ = dbservice::populate_tests(&conn);
-    let _ = dbservice::populate_questions(&conn);
-    let _ = dbservice::populate_answers(&conn);
+    dbservice::populate_tests(&conn);
+    dbservice::populate_questions(&conn);
+    dbservice::populate_answers(&conn);
     conn
 }
 
-pub fn init_conn() -> Connection {
-    // TODO move to file once schema done
-    Connection::open_in_memory().unwrap()
+pub fn init_conn(path: &Path) -> Result<Connection, Box<dyn std::error::Error>> {
+    if let Some(dir) = path.parent() && !dir.as_os_str().is_empty() {
+        fs::create_dir_all(dir)?;
+    }
+    let conn = Connection::open(path)?;
+    dbservice::create_schema(&conn)?;
+    Ok(conn)
 }
 
 pub fn get_to_do(conn: &Connection) -> Vec<(String, String)> {
-    match dbservice::get_by_status(conn, "NOT_STARTED") {
-        Ok(rows) => rows,
-        _ => vec![]
-    }
+    dbservice::get_by_status(conn, "NOT_STARTED").unwrap_or_default()
 }
 
 pub fn get_finished(conn: &Connection) -> Vec<(String, String)> {
-    match dbservice::get_by_status(conn, "FINISHED") {
-        Ok(rows) => rows,
-        _ => vec![]
-    }
+    dbservice::get_by_status(conn, "FINISHED").unwrap_or_default()
 }
 
 pub fn get_test_by_id(conn: &Connection, id: String) -> Option<test::TestModel> {
@@ -40,47 +39,564 @@ pub fn get_result_by_id(conn: &Connection, id: String) -> Option<test::ResultMod
     dbservice::get_result_by_id(conn, id)
 }
 
+pub fn get_result_by_attempt(conn: &Connection, attempt_id: String) -> Option<test::ResultModel> {
+    dbservice::get_result_by_attempt(conn, attempt_id)
+}
+
+pub fn get_attempts(conn: &Connection, id: String) -> Vec<(String, String, bool, bool)> {
+    dbservice::get_attempts(conn, id).unwrap_or_default()
+}
+
 pub fn set_finished(conn: &Connection, id: String) {
-    match dbservice::update_status(conn, id, "FINISHED") {
-        _ => () 
+    let _ = dbservice::update_status(conn, id, "FINISHED");
+}
+
+// returns the id of the stored attempt
+pub fn save_result(conn: &Connection, result: test::ResultModel) -> Option<String> {
+    dbservice::save_result(conn, result).ok()
+}
+
+pub fn save_review(conn: &Connection, result: &test::ResultModel) {
+    let _ = dbservice::save_review(conn, result, schedule::today());
+}
+
+// every question due for review on the given day as one session, it has no test id
+pub fn get_due_test(conn: &Connection, title: String, today: i32) -> Option<test::TestModel> {
+    let mut questions = dbservice::get_due_questions(conn, today).unwrap_or_default();
+    if questions.is_empty() {
+        return None;
+    }
+    // the numbers of questions from different tests would clash
+    questions.iter_mut().enumerate().for_each(|(idx, q)| q.number = idx + 1);
+    Some(test::TestModel::new(String::new(), title, questions))
+}
+
+pub fn count_due(conn: &Connection, today: i32) -> usize {
+    dbservice::count_due(conn, today).unwrap_or_default()
+}
+
+// everything shown on the statistics screen, attempts are counted over the last two weeks
+pub fn get_stats(conn: &Connection) -> stats::StatsModel {
+    let attempts = dbservice::get_attempt_scores(conn).unwrap_or_default();
+    let hardest = dbservice::get_hardest_questions(conn, 10).unwrap_or_default();
+    let tags = dbservice::get_tag_scores(conn).unwrap_or_default();
+    let average_time = dbservice::get_average_time(conn).unwrap_or_default();
+    stats::StatsModel::new(&attempts, hardest, tags, average_time, chrono::Local::now().date_naive(), 14)
+}
+
+// the test of a result with only the questions answered wrong, the result is linked to the attempt
+pub fn get_retry_test(conn: &Connection, result: &test::ResultModel) -> Option<test::TestModel> {
+    let test_m = get_test_by_id(conn, result.id.clone())?;
+    let retry = test_m.with_questions(&result.wrong_numbers());
+    if retry.questions.is_empty() {
+        return None;
+    }
+    Some(retry)
+}
+
+// every test that can be taken or was already finished
+pub fn get_exams(conn: &Connection) -> Vec<(String, String)> {
+    let mut exams = get_to_do(conn);
+    exams.extend(get_finished(conn));
+    exams.sort_by_key(|e| e.0.parse::<u64>().unwrap_or_default());
+    exams
+}
+
+pub fn get_exam_tags(conn: &Connection) -> Vec<(String, String)> {
+    dbservice::get_exam_tags(conn).unwrap_or_default()
+}
+
+pub fn get_tags(conn: &Connection) -> Vec<(String, usize)> {
+    dbservice::get_tags(conn).unwrap_or_default()
+}
+
+// questions of the chosen tests followed by the ones with the chosen tags
+pub fn get_custom_pool(conn: &Connection, custom: &builder::CustomTest) -> Vec<test::QuestionModel> {
+    let mut pool: Vec<test::QuestionModel> = custom.exams.iter()
+        .filter_map(|id| get_test_by_id(conn, id.clone()))
+        .flat_map(|t| t.questions)
+        .collect();
+    for tag in custom.tags.iter() {
+        pool.extend(dbservice::get_tagged_questions(conn, tag).unwrap_or_default());
     }
+    pool
 }
 
-pub fn save_result(conn: &Connection, result: test::ResultModel) {
-    match dbservice::save_result(conn, result) {
-        _ => () 
+// questions asked once in the custom test
+pub fn count_custom_pool(conn: &Connection, custom: &builder::CustomTest) -> usize {
+    custom.to_model(String::new(), get_custom_pool(conn, custom)).questions.len()
+}
+
+// the custom test is stored like an imported one so it shows up with the other tests, returns its id
+pub fn save_custom_test(conn: &Connection, custom: &builder::CustomTest, title: String) -> Option<String> {
+    let model = custom.to_model(title, get_custom_pool(conn, custom));
+    if model.questions.is_empty() {
+        return None;
     }
+    dbservice::save_new_test(conn, &model).ok()
+}
+
+pub fn save_progress(conn: &Connection, progress: &test::ProgressModel) {
+    let _ = dbservice::save_progress(conn, progress);
+}
+
+pub fn get_progress(conn: &Connection, id: String) -> Option<test::ProgressModel> {
+    dbservice::get_progress(conn, id)
+}
+
+pub fn get_paused(conn: &Connection) -> Vec<String> {
+    dbservice::get_paused(conn).unwrap_or_default()
 }
 
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::scoring;
 
-    // TODO create a setup with in memomy conneciton once moved to file db
+    // the test as it comes back from the database
+    fn save_and_reload(conn: &Connection, model: &test::TestModel) -> test::TestModel {
+        let id = dbservice::save_new_test(conn, model).unwrap();
+        get_test_by_id(conn, id).unwrap()
+    }
 
     #[test]
     fn test_query_by_id() {
-        let questions = vec![
-            test::QuestionModel::new(String::from(""), 
-                vec![
-                    String::from(""), 
-                    String::from(""),
-                    String::from(""),
-                    String::from(""),
-                ],
-                2),
+        let conn = init_conn_and_populate();
+
+        let result = get_test_by_id(&conn, String::from("1")).unwrap();
+
+        assert_eq!(result.id, "1");
+        assert_eq!(result.title, "[2025-03-07] English idioms with a twist");
+        assert_eq!(result.questions.len(), 2);
+        assert_eq!(result.questions[1].answers[2], "last time");
+        assert_eq!(result.questions[1].correct, vec![2]);
+    }
+
+    #[test]
+    fn test_query_by_missing_id() {
+        let conn = init_conn_and_populate();
+
+        let result = get_test_by_id(&conn, String::from("42"));
+
+        assert_eq!(result, None);
+    }
+
+    #[test]
+    fn test_file_db_keeps_data() {
+        let path = std::env::temp_dir().join(format!("ltr-test-{}.db", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let model = test::TestModel::new(String::from("0"), String::from("Saved"), vec![
+            test::QuestionModel::new(String::from("Q1"), test::QuestionKind::Single, vec![String::from("a"), String::from("b"), String::from("c"), String::from("d")], vec![1]),
+        ]);
+
+        {
+            let conn = init_conn(&path).unwrap();
+            dbservice::save_new_test(&conn, &model).unwrap();
+        }
+        let conn = init_conn(&path).unwrap();
+        let result = get_to_do(&conn);
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(result.len(), 1);
+        assert!(result[0].1.ends_with("Saved"));
+    }
+
+    #[test]
+    fn test_every_attempt_is_kept() {
+        let conn = init_conn_and_populate();
+        let model = get_test_by_id(&conn, String::from("1")).unwrap();
+        let first = model.questions.iter()
+            .map(|q| test::AnswerModel::new(q, vec![0], 3))
+            .collect();
+        let second = model.questions.iter()
+            .map(|q| test::AnswerModel::new(q, q.correct.clone(), 2))
+            .collect();
+
+        save_result(&conn, test::ResultModel::new(model.id.clone(), model.title.clone(), first, 6));
+        save_result(&conn, test::ResultModel::new(model.id.clone(), model.title.clone(), second, 4));
+        let attempts = get_attempts(&conn, model.id.clone());
+        let latest = get_result_by_id(&conn, model.id.clone()).unwrap();
+        let oldest = get_result_by_attempt(&conn, attempts[1].0.clone()).unwrap();
+
+        assert_eq!(attempts.len(), 2);
+        assert_eq!(latest.score(), 2);
+        assert_eq!(latest.total_time, 4);
+        assert_eq!(oldest.score(), 0);
+        assert_eq!(oldest.answers[0].given, vec![0]);
+    }
+
+    #[test]
+    fn test_variable_answer_count() {
+        let conn = init_conn_and_populate();
+        let model = test::TestModel::new(String::from("0"), String::from("Counts"), vec![
+            test::QuestionModel::new(String::from("TF"), test::QuestionKind::Single, vec![String::from("True"), String::from("False")], vec![1]),
+            test::QuestionModel::new(String::from("Five"), test::QuestionKind::Multiple, (1..=5).map(|i| format!("a{}", i)).collect(), vec![1, 4]),
+        ]);
+
+        let result = save_and_reload(&conn, &model);
+
+        assert_eq!(result.questions[0].answers.len(), 2);
+        assert_eq!(result.questions[1].answers.len(), 5);
+        assert_eq!(result.questions[1].answers[4], "a5");
+        assert_eq!(result.questions[1].correct, vec![1, 4]);
+        assert_eq!(result.questions[1].kind, test::QuestionKind::Multiple);
+    }
+
+    #[test]
+    fn test_multiple_choice_result_keeps_chosen_set() {
+        let conn = init_conn_and_populate();
+        let model = test::TestModel::new(String::from("0"), String::from("Forms"), vec![
+            test::QuestionModel::new(String::from("All"), test::QuestionKind::Multiple, (1..=4).map(|i| format!("a{}", i)).collect(), vec![0, 2]),
+            test::QuestionModel::new(String::from("One"), test::QuestionKind::Single, (1..=3).map(|i| format!("a{}", i)).collect(), vec![1]),
+        ]);
+        let saved = save_and_reload(&conn, &model);
+        let answers = vec![
+            test::AnswerModel::new(&saved.questions[0], vec![0, 3], 5),
+            test::AnswerModel::new(&saved.questions[1], vec![], 5),
         ];
-        let expected = Some(test::TestModel::new(
-                String::from("1"), 
-                String::from("[2025-03-07] English idioms with a twist"), 
-                questions
-        ));
-        let conn = init_table();
 
-        let result = get_q_by_id(&conn, String::from("1"));
+        save_result(&conn, test::ResultModel::new(saved.id.clone(), saved.title.clone(), answers, 10));
+        let result = get_result_by_id(&conn, saved.id).unwrap();
 
-        assert_eq!(result, expected);
+        assert_eq!(result.answers[0].given, vec![0, 3]);
+        assert!(!result.answers[0].is_correct);
+        assert_eq!(result.answers[0].score, 0.0);
+        assert_eq!(result.answers[1].given, Vec::<usize>::new());
     }
-}
 
+    #[test]
+    fn test_typed_result_keeps_text_and_match() {
+        let conn = init_conn_and_populate();
+        let model = test::TestModel::new(String::from("0"), String::from("Verbs"), vec![
+            test::QuestionModel::new(String::from("write"), test::QuestionKind::Typed, vec![String::from("written")], vec![0]),
+        ]);
+        let saved = save_and_reload(&conn, &model);
+        let options = crate::models::matching::MatchOptions::default();
+        let answers = vec![test::AnswerModel::typed(&saved.questions[0], String::from("Writen"), &options, 5)];
+
+        save_result(&conn, test::ResultModel::new(saved.id.clone(), saved.title.clone(), answers, 5));
+        let result = get_result_by_id(&conn, saved.id).unwrap();
+
+        assert_eq!(result.answers[0].typed, "Writen");
+        assert_eq!(result.answers[0].matched, crate::models::matching::MatchKind::Typo);
+        assert!(result.answers[0].is_correct);
+    }
+
+    #[test]
+    fn test_cloze_result_keeps_every_gap() {
+        let conn = init_conn_and_populate();
+        let gaps = vec![
+            test::GapModel::new(false, vec![String::from("was"), String::from("were")], vec![1]),
+            test::GapModel::new(true, vec![String::from("dumb")], vec![0]),
+        ];
+        let model = test::TestModel::new(String::from("0"), String::from("Gaps"), vec![
+            test::QuestionModel::cloze(String::from("I wish you {1} so {2}."), gaps),
+        ]);
+        let saved = save_and_reload(&conn, &model);
+        let q = &saved.questions[0];
+        let options = crate::models::matching::MatchOptions::default();
+        let gap_answers = vec![
+            test::GapAnswer::choice(&q.gaps[0], Some(1)),
+            test::GapAnswer::typed(&q.gaps[1], String::from("smart"), &options),
+        ];
+        let answers = vec![test::AnswerModel::cloze(q, gap_answers, 7)];
+
+        save_result(&conn, test::ResultModel::new(saved.id.clone(), saved.title.clone(), answers, 7));
+        let result = get_result_by_id(&conn, saved.id).unwrap();
+
+        assert_eq!(q.gaps, model.questions[0].gaps);
+        assert_eq!(result.answers[0].gaps.len(), 2);
+        assert_eq!(result.answers[0].score, 0.5);
+        assert_eq!(result.answers[0].filled_question(), "I wish you [were] so [smart].");
+    }
+
+    #[test]
+    fn test_explanation_kept_in_results() {
+        let conn = init_conn_and_populate();
+        let mut question = test::QuestionModel::new(String::from("TF"), test::QuestionKind::Single, vec![String::from("True"), String::from("False")], vec![1]);
+        question.explanation = String::from("Less is less.");
+        let model = test::TestModel::new(String::from("0"), String::from("Why"), vec![question]);
+        let saved = save_and_reload(&conn, &model);
+        let answers = vec![test::AnswerModel::new(&saved.questions[0], vec![0], 3)];
+
+        save_result(&conn, test::ResultModel::new(saved.id.clone(), saved.title.clone(), answers, 3));
+        let result = get_result_by_id(&conn, saved.id).unwrap();
+
+        assert_eq!(saved.questions[0].explanation, "Less is less.");
+        assert_eq!(result.answers[0].explanation, "Less is less.");
+    }
+
+    #[test]
+    fn test_shuffled_attempt_saved_in_test_order() {
+        let conn = init_conn_and_populate();
+        let mut model = test::TestModel::new(String::from("0"), String::from("Shuffled"), vec![
+            test::QuestionModel::new(String::from("First"), test::QuestionKind::Single, vec![String::from("a"), String::from("b")], vec![0]),
+            test::QuestionModel::new(String::from("Second"), test::QuestionKind::Single, vec![String::from("c"), String::from("d")], vec![1]),
+        ]);
+        model.shuffle = Some(true);
+        let saved = save_and_reload(&conn, &model);
+        // asked in reverse order
+        let answers = vec![
+            test::AnswerModel::new(&saved.questions[1], vec![1], 2),
+            test::AnswerModel::new(&saved.questions[0], vec![1], 3),
+        ];
+
+        save_result(&conn, test::ResultModel::new(saved.id.clone(), saved.title.clone(), answers, 5));
+        let result = get_result_by_id(&conn, saved.id).unwrap();
+
+        assert_eq!(saved.shuffle, Some(true));
+        assert_eq!(result.answers[0].question, "First");
+        assert_eq!(result.answers[0].given, vec![1]);
+        assert!(!result.answers[0].is_correct);
+        assert_eq!(result.answers[1].question, "Second");
+        assert!(result.answers[1].is_correct);
+    }
+
+    #[test]
+    fn test_timed_out_answer_marked() {
+        let conn = init_conn_and_populate();
+        let mut model = test::TestModel::new(String::from("0"), String::from("Timed"), vec![
+            test::QuestionModel::new(String::from("TF"), test::QuestionKind::Single, vec![String::from("True"), String::from("False")], vec![1]),
+        ]);
+        model.question_limit = Some(10);
+        let saved = save_and_reload(&conn, &model);
+        let mut answer = test::AnswerModel::new(&saved.questions[0], vec![], 10);
+        answer.timed_out = true;
+
+        save_result(&conn, test::ResultModel::new(saved.id.clone(), saved.title.clone(), vec![answer], 10));
+        let result = get_result_by_id(&conn, saved.id).unwrap();
+
+        assert_eq!(saved.question_limit, Some(10));
+        assert_eq!(saved.time_limit, None);
+        assert!(result.answers[0].timed_out);
+    }
+
+    #[test]
+    fn test_paused_attempt_resumed_and_replaced() {
+        let conn = init_conn_and_populate();
+        let saved = get_test_by_id(&conn, String::from("1")).unwrap();
+        let answered = vec![test::AnswerModel::new(&saved.questions[1], vec![2], 4)];
+        let result = test::ResultModel::new(saved.id.clone(), saved.title.clone(), answered, 30);
+
+        let mut paused = test::ProgressModel::new(result, vec![1, 0], 6);
+        paused.flagged = vec![1];
+        paused.current = Some(1);
+        save_progress(&conn, &paused);
+        let progress = get_progress(&conn, saved.id.clone()).unwrap();
+
+        assert_eq!(get_paused(&conn), vec![saved.id.clone()]);
+        assert_eq!(progress.question_order, vec![1, 0]);
+        assert_eq!(progress.question_time, 6);
+        assert_eq!(progress.flagged, vec![1]);
+        assert_eq!(progress.current, Some(1));
+        assert_eq!(progress.result.total_time, 30);
+        assert_eq!(progress.result.answers[0].number, 2);
+        assert!(get_attempts(&conn, saved.id.clone()).is_empty());
+
+        let mut finished = progress.result.clone();
+        finished.answers.push(test::AnswerModel::new(&saved.questions[0], vec![3], 5));
+        save_result(&conn, finished);
+
+        assert!(get_progress(&conn, saved.id.clone()).is_none());
+        assert!(get_paused(&conn).is_empty());
+        assert_eq!(get_attempts(&conn, saved.id).len(), 1);
+    }
+
+    #[test]
+    fn test_practice_attempt_kept_apart() {
+        let conn = init_conn_and_populate();
+        let saved = get_test_by_id(&conn, String::from("1")).unwrap();
+        let answers = saved.questions.iter()
+            .map(|q| test::AnswerModel::new(q, q.correct.clone(), 2))
+            .collect();
+        let mut practice = test::ResultModel::new(saved.id.clone(), saved.title.clone(), answers, 4);
+        practice.practice = true;
+
+        save_result(&conn, practice);
+        let attempts = get_attempts(&conn, saved.id.clone());
+        let result = get_result_by_attempt(&conn, attempts[0].0.clone()).unwrap();
+
+        assert_eq!(attempts.len(), 1);
+        assert!(attempts[0].2);
+        assert!(result.practice);
+        assert!(get_result_by_id(&conn, saved.id).is_none());
+    }
+
+    #[test]
+    fn test_retry_linked_to_attempt() {
+        let conn = init_conn_and_populate();
+        let saved = get_test_by_id(&conn, String::from("1")).unwrap();
+        let answers = saved.questions.iter()
+            .map(|q| test::AnswerModel::new(q, vec![], 2))
+            .collect();
+        let mut result = test::ResultModel::new(saved.id.clone(), saved.title.clone(), answers, 4);
+        result.attempt = save_result(&conn, result.clone());
+
+        let retry_test = get_retry_test(&conn, &result).unwrap();
+        let retry_answers = retry_test.questions.iter()
+            .map(|q| test::AnswerModel::new(q, q.correct.clone(), 1))
+            .collect();
+        let mut retry = test::ResultModel::new(saved.id.clone(), saved.title.clone(), retry_answers, 2);
+        retry.retry_of = result.attempt.clone();
+        let retry_id = save_result(&conn, retry).unwrap();
+        let attempts = get_attempts(&conn, saved.id.clone());
+
+        assert_eq!(retry_test.questions.len(), saved.questions.len());
+        assert_eq!(attempts.len(), 2);
+        assert_eq!(attempts.iter().filter(|a| a.3).count(), 1);
+        assert_eq!(get_result_by_attempt(&conn, retry_id).unwrap().retry_of, result.attempt);
+        assert_eq!(get_result_by_id(&conn, saved.id).unwrap().attempt, result.attempt);
+    }
+
+    #[test]
+    fn test_answers_scheduled_for_review() {
+        let conn = init_conn_and_populate();
+        let saved = get_test_by_id(&conn, String::from("1")).unwrap();
+        let answers = saved.questions.iter()
+            .enumerate()
+            .map(|(idx, q)| test::AnswerModel::new(q, if idx == 0 { q.correct.clone() } else { vec![] }, 2))
+            .collect();
+        let today = schedule::today();
+
+        save_result(&conn, test::ResultModel::new(saved.id.clone(), saved.title.clone(), answers, 4));
+        let due = get_due_test(&conn, String::from("review"), today + 1).unwrap();
+
+        assert!(get_due_test(&conn, String::from("review"), today).is_none());
+        assert_eq!(count_due(&conn, today + 1), saved.questions.len());
+        assert!(due.questions.iter().all(|q| !q.id.is_empty()));
+        assert!(due.id.is_empty());
+
+        let review_answers = due.questions.iter()
+            .map(|q| test::AnswerModel::new(q, q.correct.clone(), 1))
+            .collect();
+        dbservice::save_review(&conn, &test::ResultModel::new(String::new(), due.title, review_answers, 2), today + 1).unwrap();
+
+        assert_eq!(count_due(&conn, today + 1), 0);
+        assert_eq!(get_attempts(&conn, saved.id).len(), 1);
+    }
+
+    #[test]
+    fn test_stats_from_finished_attempts() {
+        let conn = init_conn_and_populate();
+        let saved = get_test_by_id(&conn, String::from("1")).unwrap();
+        let answers = |first_correct: bool| saved.questions.iter()
+            .enumerate()
+            .map(|(idx, q)| test::AnswerModel::new(q, if idx == 0 && first_correct { q.correct.clone() } else { vec![] }, 4))
+            .collect::<Vec<test::AnswerModel>>();
+
+        save_result(&conn, test::ResultModel::new(saved.id.clone(), saved.title.clone(), answers(true), 8));
+        save_result(&conn, test::ResultModel::new(saved.id.clone(), saved.title.clone(), answers(false), 8));
+        let paused = test::ResultModel::new(saved.id.clone(), saved.title.clone(), answers(false), 8);
+        save_progress(&conn, &test::ProgressModel::new(paused, vec![0, 1], 0));
+        let mut practice = test::ResultModel::new(saved.id.clone(), saved.title.clone(), answers(true), 8);
+        practice.practice = true;
+        save_result(&conn, practice);
+        let stats = get_stats(&conn);
+
+        let exam = stats.exams.iter().find(|e| e.exam == saved.title).unwrap();
+
+        // the mock data holds four attempts from long ago
+        assert_eq!(stats.attempt_count(), 6);
+        assert_eq!(stats.attempts_per_day.len(), 14);
+        assert_eq!(stats.attempts_per_day.last().unwrap().1, 2);
+        assert_eq!((exam.attempts, exam.best, exam.last), (2, 50, 0));
+        assert_eq!(stats.hardest[0].error_rate, 1.0);
+        assert!(stats.hardest.windows(2).all(|w| w[0].error_rate >= w[1].error_rate));
+    }
+
+    #[test]
+    fn test_grade_kept_with_attempt() {
+        let conn = init_conn_and_populate();
+        let mut model = get_test_by_id(&conn, String::from("1")).unwrap();
+        model.questions[0].points = 3.0;
+        model.pass_threshold = Some(50.0);
+        model.grade_scale = Some(String::from("letters"));
+        let saved = save_and_reload(&conn, &model);
+        let answers = saved.questions.iter()
+            .enumerate()
+            .map(|(idx, q)| test::AnswerModel::new(q, if idx == 0 { q.correct.clone() } else { vec![] }, 2))
+            .collect::<Vec<test::AnswerModel>>();
+        let scoring = scoring::Scoring { negative: 0.0, pass_threshold: saved.pass_threshold, scale: scoring::GradeScale::from_code("letters") };
+        let mut result = test::ResultModel::new(saved.id.clone(), saved.title.clone(), answers, 4);
+        result.grade = Some(scoring.grade(&result.answers));
+
+        save_result(&conn, result.clone());
+        let loaded = get_result_by_id(&conn, saved.id.clone()).unwrap();
+        let attempts = get_attempts(&conn, saved.id);
+
+        assert_eq!(saved.questions[0].points, 3.0);
+        assert_eq!(saved.grade_scale, Some(String::from("letters")));
+        assert_eq!(loaded.grade, result.grade);
+        assert_eq!(loaded.answers[0].points, 3.0);
+        assert_eq!(loaded.grade.unwrap().percent(), 75.0);
+        assert!(attempts[0].1.ends_with(" C"));
+    }
+
+    #[test]
+    fn test_sample_keeps_drawn_questions() {
+        let conn = init_conn_and_populate();
+        let mut model = get_test_by_id(&conn, String::from("1")).unwrap();
+        model.sample_size = Some(2);
+        model.quotas = vec![(String::from("verbs"), 1)];
+        model.questions[1].tags = vec![String::from("verbs")];
+        let saved = save_and_reload(&conn, &model);
+        let drawn = saved.draw_sample(2, &mut rand::thread_rng());
+        let answers = drawn.iter()
+            .map(|idx| test::AnswerModel::new(&saved.questions[*idx], vec![], 1))
+            .collect::<Vec<test::AnswerModel>>();
+
+        save_result(&conn, test::ResultModel::new(saved.id.clone(), saved.title.clone(), answers, 2));
+        let loaded = get_result_by_id(&conn, saved.id.clone()).unwrap();
+
+        assert_eq!(saved.sample_size, Some(2));
+        assert_eq!(saved.quotas, model.quotas);
+        assert_eq!(saved.questions[1].tags, vec![String::from("verbs")]);
+        assert!(drawn.contains(&1));
+        assert_eq!(loaded.answers.iter().map(|a| a.number - 1).collect::<Vec<usize>>(), drawn);
+    }
+
+    #[test]
+    fn test_custom_test_saved_from_exams_and_tags() {
+        let conn = init_conn_and_populate();
+        let mut tagged = get_test_by_id(&conn, String::from("2")).unwrap();
+        tagged.questions[0].tags = vec![String::from("verbs")];
+        dbservice::save_new_test(&conn, &tagged).unwrap();
+        let first = get_test_by_id(&conn, String::from("1")).unwrap();
+        let custom = builder::CustomTest { exams: vec![String::from("1")], tags: vec![String::from("verbs")], count: 2, ..Default::default() };
+
+        let tags = get_tags(&conn);
+        let id = save_custom_test(&conn, &custom, String::from("Mixed")).unwrap();
+        let saved = get_test_by_id(&conn, id.clone()).unwrap();
+
+        assert_eq!(tags, vec![(String::from("verbs"), 1)]);
+        assert_eq!(count_custom_pool(&conn, &custom), first.questions.len() + 1);
+        assert_eq!(saved.questions.len(), first.questions.len() + 1);
+        assert_eq!(saved.questions.last().unwrap().question, tagged.questions[0].question);
+        assert_eq!(saved.sample_size, Some(2));
+        assert!(get_to_do(&conn).iter().any(|t| t.0 == id));
+    }
+
+    #[test]
+    fn test_exam_tags_kept_and_grouped_in_stats() {
+        let conn = init_conn_and_populate();
+        let mut model = get_test_by_id(&conn, String::from("1")).unwrap();
+        model.tags = vec![String::from("idioms")];
+        model.questions[0].tags = vec![String::from("verbs")];
+        let id = dbservice::save_new_test(&conn, &model).unwrap();
+        let saved = get_test_by_id(&conn, id.clone()).unwrap();
+        let answers = saved.questions.iter()
+            .map(|q| test::AnswerModel::new(q, q.correct.clone(), 1))
+            .collect::<Vec<test::AnswerModel>>();
+
+        save_result(&conn, test::ResultModel::new(id.clone(), saved.title.clone(), answers, 3));
+        let tagged = get_exam_tags(&conn);
+        let tags = get_stats(&conn).tags;
+
+        assert_eq!(saved.tags, vec![String::from("idioms")]);
+        assert_eq!(tagged, vec![(id.clone(), String::from("idioms")), (id, String::from("verbs"))]);
+        assert_eq!(tags.iter().map(|t| (t.tag.as_str(), t.answered)).collect::<Vec<_>>(),
+            vec![("idioms", saved.questions.len()), ("verbs", 1)]);
+        assert!(tags.iter().all(|t| t.score_rate == 1.0));
+    }
+}
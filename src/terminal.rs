@@ -1,6 +1,7 @@
 use crate::app::App;
 
 use std::{io, time::{Duration, Instant}};
+use rusqlite::Connection;
 use tui::{
     backend::{Backend,CrosstermBackend},
     Terminal
@@ -12,7 +13,7 @@ use crossterm::{
 };
 
 
-pub fn run() -> Result<(), io::Error> {
+pub fn run(conn: Connection) -> Result<(), io::Error> {
     
     //setup terminal
     enable_raw_mode()?;
@@ -22,7 +23,7 @@ pub fn run() -> Result<(), io::Error> {
     let mut terminal = Terminal::new(backend)?;
 
     // TODO add refresh rate as parameter
-    let result = run_app(&mut terminal, Duration::from_millis(250));
+    let result = run_app(&mut terminal, conn, Duration::from_millis(250));
 
     //restore terminal
     disable_raw_mode()?;
@@ -38,11 +39,12 @@ pub fn run() -> Result<(), io::Error> {
 
 fn run_app<B: Backend>(
         terminal: &mut Terminal<B>,
+        conn: Connection,
         tick_rate: Duration,
     ) -> Result<(), io::Error> {
 
     let mut last_tick = Instant::now();
-    let mut app = App::new();
+    let mut app = App::new(conn);
     loop {
         terminal.draw(|f| app.draw(f))?;
 
@@ -62,6 +64,7 @@ fn run_app<B: Backend>(
         }
 
         if last_tick.elapsed() >= tick_rate {
+            app.on_tick();
             last_tick = Instant::now();
         }
     }
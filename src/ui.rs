@@ -2,3 +2,6 @@ pub mod menu;
 pub mod layout;
 pub mod navbar;
 
+pub mod review;
+pub mod charts;
+pub mod filter;
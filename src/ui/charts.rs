@@ -0,0 +1,123 @@
+use crate::models::stats::{ExamStats, QuestionStats, TagStats};
+
+use tui::{
+    layout::Constraint,
+    style::{Color, Modifier, Style},
+    widgets::{BarChart, Block, Borders, Cell, Row, Sparkline, Table},
+};
+use rust_i18n::t;
+
+fn get_block(title: String) -> Block<'static> {
+    Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .style(Style::default().fg(Color::White).bg(Color::Black))
+}
+
+pub fn get_attempts_chart<'a>(data: &'a [(&'a str, u64)], locale: &str) -> BarChart<'a> {
+    BarChart::default()
+        .block(get_block(t!("stats.per_day", locale = locale).to_string()))
+        .data(data)
+        .bar_width(5)
+        .bar_gap(1)
+        .bar_style(Style::default().fg(Color::Blue))
+        .value_style(Style::default().fg(Color::White).bg(Color::Blue).add_modifier(Modifier::BOLD))
+        .label_style(Style::default().fg(Color::White))
+}
+
+// scores are in percent so the scale is fixed
+pub fn get_score_trend<'a>(data: &'a [u64], locale: &str) -> Sparkline<'a> {
+    Sparkline::default()
+        .block(get_block(t!("stats.trend", locale = locale).to_string()))
+        .data(data)
+        .max(100)
+        .style(Style::default().fg(Color::Green))
+}
+
+fn get_header_row<'a>(cells: Vec<String>) -> Row<'a> {
+    Row::new(cells)
+        .style(Style::default()
+            .add_modifier(Modifier::BOLD)
+            .fg(Color::Black)
+            .bg(Color::White)
+            )
+        .bottom_margin(1)
+}
+
+pub fn get_hardest_table<'a>(questions: &[QuestionStats], locale: &str) -> Table<'a> {
+    let rows = questions.iter()
+        .map(|q| Row::new(vec![
+            Cell::from(q.question.clone()),
+            Cell::from(q.exam.clone()),
+            Cell::from(format!("{:.0}%", q.error_rate * 100.0)),
+            Cell::from(format!("{}", q.answered)),
+        ]).style(Style::default().fg(Color::White)))
+        .collect::<Vec<Row>>();
+
+    Table::new(rows)
+        .header(get_header_row(vec![
+            t!("stats.header.question", locale = locale).to_string(),
+            t!("stats.header.exam", locale = locale).to_string(),
+            t!("stats.header.errors", locale = locale).to_string(),
+            t!("stats.header.answered", locale = locale).to_string(),
+        ]))
+        .block(get_block(t!("stats.hardest", locale = locale).to_string()))
+        .widths(&[
+            Constraint::Percentage(45),
+            Constraint::Percentage(30),
+            Constraint::Percentage(10),
+            Constraint::Percentage(15),
+        ])
+        .column_spacing(1)
+}
+
+pub fn get_tags_table<'a>(tags: &[TagStats], locale: &str) -> Table<'a> {
+    let rows = tags.iter()
+        .map(|t| Row::new(vec![
+            Cell::from(format!("#{}", t.tag)),
+            Cell::from(format!("{}", t.answered)),
+            Cell::from(format!("{:.0}%", t.score_rate * 100.0)),
+        ]).style(Style::default().fg(Color::White)))
+        .collect::<Vec<Row>>();
+
+    Table::new(rows)
+        .header(get_header_row(vec![
+            t!("stats.header.tag", locale = locale).to_string(),
+            t!("stats.header.answered", locale = locale).to_string(),
+            t!("stats.header.score", locale = locale).to_string(),
+        ]))
+        .block(get_block(t!("stats.tags", locale = locale).to_string()))
+        .widths(&[
+            Constraint::Percentage(55),
+            Constraint::Percentage(25),
+            Constraint::Percentage(20),
+        ])
+        .column_spacing(1)
+}
+
+pub fn get_exams_table<'a>(exams: &[ExamStats], locale: &str) -> Table<'a> {
+    let rows = exams.iter()
+        .map(|e| Row::new(vec![
+            Cell::from(e.exam.clone()),
+            Cell::from(format!("{}", e.attempts)),
+            Cell::from(format!("{}%", e.best)),
+            Cell::from(format!("{}%", e.last)),
+        ]).style(Style::default().fg(Color::White)))
+        .collect::<Vec<Row>>();
+
+    Table::new(rows)
+        .header(get_header_row(vec![
+            t!("stats.header.exam", locale = locale).to_string(),
+            t!("stats.header.attempts", locale = locale).to_string(),
+            t!("stats.header.best", locale = locale).to_string(),
+            t!("stats.header.last", locale = locale).to_string(),
+        ]))
+        .block(get_block(t!("stats.exams", locale = locale).to_string()))
+        .widths(&[
+            Constraint::Percentage(55),
+            Constraint::Percentage(15),
+            Constraint::Percentage(15),
+            Constraint::Percentage(15),
+        ])
+        .column_spacing(1)
+}
@@ -0,0 +1,88 @@
+// tag picked to narrow a list of tests, cycled through all the known tags and back to no filter
+pub struct TagFilter {
+    // test id and tag pairs
+    tagged: Vec<(String, String)>,
+    tags: Vec<String>,
+    pub current: Option<String>,
+}
+
+impl TagFilter {
+    pub fn new(tagged: Vec<(String, String)>) -> Self {
+        let mut filter = TagFilter { tagged: vec![], tags: vec![], current: None };
+        filter.set_tagged(tagged);
+        filter
+    }
+
+    // the current tag is dropped when no test has it anymore
+    pub fn set_tagged(&mut self, tagged: Vec<(String, String)>) {
+        let mut tags: Vec<String> = tagged.iter().map(|t| t.1.clone()).collect();
+        tags.sort();
+        tags.dedup();
+        self.current = self.current.take().filter(|c| tags.contains(c));
+        self.tagged = tagged;
+        self.tags = tags;
+    }
+
+    pub fn next(&mut self) {
+        self.current = match &self.current {
+            None => self.tags.first().cloned(),
+            Some(current) => self.tags.iter()
+                .skip_while(|t| *t != current)
+                .nth(1)
+                .cloned(),
+        };
+    }
+
+    pub fn matches(&self, id: &str) -> bool {
+        match &self.current {
+            None => true,
+            Some(tag) => self.tagged.iter().any(|t| t.0 == id && t.1 == *tag),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tags.is_empty()
+    }
+}
+
+
+#[cfg(test)]
+mod units {
+    use super::*;
+
+    fn tagged() -> Vec<(String, String)> {
+        vec![
+            (String::from("1"), String::from("verbs")),
+            (String::from("2"), String::from("b2")),
+            (String::from("1"), String::from("b2")),
+        ]
+    }
+
+    #[test]
+    fn test_cycle_through_tags() {
+        let mut filter = TagFilter::new(tagged());
+
+        filter.next();
+        let first = filter.current.clone();
+        filter.next();
+        let second = filter.current.clone();
+        filter.next();
+
+        assert_eq!(first, Some(String::from("b2")));
+        assert_eq!(second, Some(String::from("verbs")));
+        assert_eq!(filter.current, None);
+    }
+
+    #[test]
+    fn test_matches_tagged_tests() {
+        let mut filter = TagFilter::new(tagged());
+        filter.next();
+        filter.next();
+
+        assert!(filter.matches("1"));
+        assert!(!filter.matches("2"));
+
+        filter.set_tagged(vec![(String::from("2"), String::from("b2"))]);
+        assert!(filter.matches("1"));
+    }
+}
@@ -1,4 +1,5 @@
 use crate::models::test::AnswerModel;
+use crate::models::matching::MatchKind;
 
 use tui::{
     layout::{Alignment, Constraint, Direction, Layout, Rect},
@@ -66,6 +67,16 @@ pub fn get_two_row_layout(f: Rect, first: u16) -> Vec<Rect> {
         .split(f)
 }
 
+pub fn get_two_column_layout(f: Rect, first: u16) -> Vec<Rect> {
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage(first),
+            Constraint::Percentage(100 - first)
+            ].as_ref())
+        .split(f)
+}
+
 pub fn get_background() -> Block<'static> {
     Block::default()
         .style(Style::default().bg(Color::Black))
@@ -73,7 +84,7 @@ pub fn get_background() -> Block<'static> {
 
 pub fn create_navigable_list<'a>(items: Vec<String>) -> List<'a> {
     let list_items: Vec<ListItem> = items.into_iter()
-        .map(|i| ListItem::new(i))
+        .map(ListItem::new)
         .collect();
     List::new(list_items)
         .block(Block::default().borders(Borders::TOP | Borders::BOTTOM))
@@ -164,15 +175,51 @@ fn get_navbar_element(text: String, primary: bool) -> Span<'static> {
     }
 }
 
-pub fn get_question_area<'a>(q_text: &'a str, qidx: usize, total: usize, q_time: u64, t_time: u64, locale: &str) -> Paragraph<'a> {
+pub fn get_checkbox_item(text: String, checked: bool) -> String {
+    if checked {
+        format!("[x] {}", text)
+    } else {
+        format!("[ ] {}", text)
+    }
+}
+
+pub fn get_typed_answer_area(typed: &str) -> Paragraph<'static> {
+    let txt = vec![
+        Spans::from(Span::raw("")),
+        Spans::from(vec![
+            Span::raw(">> "),
+            Span::styled(format!("{}_", typed), Style::default().bg(Color::Blue).add_modifier(Modifier::BOLD)),
+        ]),
+    ];
+
+    Paragraph::new(txt)
+        .block(Block::default().borders(Borders::TOP | Borders::BOTTOM))
+        .style(Style::default().fg(Color::White).bg(Color::Black))
+        .alignment(Alignment::Left)
+        .wrap(Wrap { trim: false })
+}
+
+// a timer counts up unless there is a time limit, then it counts down
+pub enum Clock {
+    Elapsed(u64),
+    Remaining(u64),
+}
+
+pub fn get_question_area<'a>(q_text: &'a str, hints: Vec<String>, qidx: usize, total: usize, q_clock: Clock, t_clock: Clock, locale: &str) -> Paragraph<'a> {
     let header = format!("{} {} {} {}", 
         t!("question.text", locale = locale), 
         qidx, 
         t!("question.outof", locale = locale), 
         total);
-    let q_timer = format!("{}: {}", t!("question.time.q", locale = locale), format_time(q_time));
-    let t_timer = format!("{}: {}", t!("question.time.t", locale = locale), format_time(t_time));
-    let txt = vec![
+    let q_timer = match q_clock {
+        Clock::Elapsed(time) => format!("{}: {}", t!("question.time.q", locale = locale), format_time(time)),
+        Clock::Remaining(time) => format!("{}: {}", t!("question.left.q", locale = locale), format_time(time)),
+    };
+    let t_timer = match t_clock {
+        Clock::Elapsed(time) => format!("{}: {}", t!("question.time.t", locale = locale), format_time(time)),
+        Clock::Remaining(time) => format!("{}: {}", t!("question.left.t", locale = locale), format_time(time)),
+    };
+    let mut txt = vec![
         Spans::from(Span::raw("")),
         Spans::from(Span::styled(header, Style::default().add_modifier(Modifier::BOLD))),
         Spans::from(Span::raw("----------")),
@@ -183,6 +230,8 @@ pub fn get_question_area<'a>(q_text: &'a str, qidx: usize, total: usize, q_time:
         Spans::from(Span::styled(q_text, Style::default().bg(Color::White).fg(Color::Black))),
         Spans::from(Span::raw("")),
     ];
+    hints.into_iter()
+        .for_each(|h| txt.push(Spans::from(Span::styled(h, Style::default().add_modifier(Modifier::ITALIC)))));
 
     Paragraph::new(txt)
         .block(Block::default()
@@ -193,31 +242,100 @@ pub fn get_question_area<'a>(q_text: &'a str, qidx: usize, total: usize, q_time:
         .wrap(Wrap { trim: true })
 }
 
+// one cell per question, (answered, flagged), ten questions in a row
+pub fn get_overview_grid<'a>(cells: Vec<(bool, bool)>, cursor: usize, locale: &str) -> Paragraph<'a> {
+    let unanswered = cells.iter().filter(|c| !c.0).count();
+    let mut txt = vec![
+        Spans::from(Span::raw("")),
+        Spans::from(Span::styled(t!("overview.title", locale = locale).to_string(), Style::default().add_modifier(Modifier::BOLD))),
+        Spans::from(Span::raw("")),
+    ];
+    let rows = cells.chunks(10)
+        .enumerate()
+        .map(|(row, chunk)| {
+            let spans = chunk.iter()
+                .enumerate()
+                .flat_map(|(col, (answered, flagged))| {
+                    let idx = row * 10 + col;
+                    let mut style = match (answered, flagged) {
+                        (_, true) => Style::default().bg(Color::Yellow).fg(Color::Black),
+                        (true, false) => Style::default().bg(Color::Green).fg(Color::White),
+                        (false, false) => Style::default().bg(Color::DarkGray).fg(Color::White),
+                    };
+                    if idx == cursor {
+                        style = style.add_modifier(Modifier::BOLD | Modifier::REVERSED);
+                    }
+                    vec![Span::styled(format!(" {:>3} ", idx + 1), style), Span::raw(" ")]
+                })
+                .collect::<Vec<Span>>();
+            Spans::from(spans)
+        })
+        .collect::<Vec<Spans>>();
+    rows.into_iter().for_each(|r| {
+        txt.push(r);
+        txt.push(Spans::from(Span::raw("")));
+    });
+    txt.push(Spans::from(vec![
+        Span::styled(format!(" {} ", t!("overview.answered", locale = locale)), Style::default().bg(Color::Green).fg(Color::White)),
+        Span::raw(" "),
+        Span::styled(format!(" {} ", t!("overview.unanswered", locale = locale)), Style::default().bg(Color::DarkGray).fg(Color::White)),
+        Span::raw(" "),
+        Span::styled(format!(" {} ", t!("overview.flagged", locale = locale)), Style::default().bg(Color::Yellow).fg(Color::Black)),
+    ]));
+    txt.push(Spans::from(Span::raw("")));
+    txt.push(Spans::from(Span::styled(
+        t!("overview.submit", locale = locale, count = unanswered).to_string(),
+        Style::default().add_modifier(Modifier::ITALIC))));
+
+    Paragraph::new(txt)
+        .block(Block::default()
+            .borders(Borders::NONE)
+        )
+        .style(Style::default().bg(Color::Black))
+        .alignment(Alignment::Left)
+        .wrap(Wrap { trim: false })
+}
+
 pub fn render_summary_table<'a>(answers: Vec<AnswerModel>, locale: &str) -> Table<'a> {
     let mut idx = 0;
     let font_color = Color::White;
     let rows: Vec<Row> = answers.iter()
         .map(|a| {
-            let mut correct = "summary.no";
-            if a.is_correct {
-                correct = "summary.yes"; 
+            let mut correct = t!("summary.no", locale = locale).to_string();
+            let mut color = Color::Red;
+            if a.matched == MatchKind::Typo {
+                correct = format!("{} ({})", t!("summary.yes", locale = locale), t!("summary.typo", locale = locale));
+                color = Color::Green;
+            } else if a.is_correct {
+                correct = t!("summary.yes", locale = locale).to_string();
+                color = Color::Green;
+            } else if a.score > 0.0 {
+                correct = format!("{} ({:.0}%)", t!("summary.partial", locale = locale), a.score * 100.0);
+                color = Color::Yellow;
+            }
+            if a.timed_out {
+                correct = format!("{} ({})", correct, t!("summary.timeout", locale = locale));
             }
             idx += 1;
-            (format!(" #{:?}", idx), a.question.to_string(), correct, format_time(a.time))
+            // the explanation is only worth reading when the answer was not right
+            let explanation = if a.is_correct { String::new() } else { a.explanation.clone() };
+            (format!(" #{:?}", idx), a.question.to_string(), correct, format_time(a.time), color, explanation)
         })
         .map(|t| {
-	    let mut color = Color::Red;
-	    if t.2 == "summary.yes" {
-        	color = Color::Green;
-	    }
-	    let result = Spans::from(vec![Span::raw(" "), Span::raw(t!(t.2, locale = locale))]);
+	    let color = t.4;
+	    let result = Spans::from(vec![Span::raw(" "), Span::raw(t.2)]);
 	    let timer = Spans::from(vec![Span::raw(" "), Span::raw(t.3)]);
+	    let mut question = vec![Spans::from(Span::raw(t.1))];
+	    if !t.5.is_empty() {
+		question.push(Spans::from(Span::styled(format!("  {}", t.5), Style::default().add_modifier(Modifier::ITALIC))));
+	    }
+	    let height = question.len() as u16;
 	    Row::new(vec![
 		Cell::from(t.0).style(Style::default().bg(color).fg(font_color)),
-		Cell::from(t.1).style(Style::default().bg(color).fg(font_color)),
+		Cell::from(question).style(Style::default().bg(color).fg(font_color)),
 		Cell::from(timer).style(Style::default().bg(color).fg(font_color)),
 		Cell::from(result).style(Style::default().bg(color).fg(font_color)),
-	    ]).height(1)
+	    ]).height(height)
 	    .bottom_margin(1)
 	})
         .collect();
@@ -9,7 +9,7 @@ pub struct Menu {
 impl Menu {
     pub fn new(items: Vec<String>) -> Self {
         let mut state = ListState::default();
-        if items.len() > 0 {
+        if !items.is_empty() {
             state.select(Some(0));
         }
         Menu { state, items }
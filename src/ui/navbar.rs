@@ -12,6 +12,18 @@ pub enum NavType {
     Rerun,
     Results,
     Import,
+    History,
+    Flag,
+    Overview,
+    Pause,
+    Submit,
+    Open,
+    Practice,
+    Retry,
+    Toggle,
+    Order,
+    Create,
+    Filter,
 }
 
 pub fn get_elements(items: Vec<NavType>, locale: String) -> Vec<(String, String)> {
@@ -37,6 +49,18 @@ fn get_text(nav: &NavType, locale: &str) -> String {
         NavType::Rerun => t!("navigation.rerun", locale = locale).to_string(),
         NavType::Results => t!("navigation.results", locale = locale).to_string(),
         NavType::Import => t!("navigation.import", locale = locale).to_string(),
+        NavType::History => t!("navigation.history", locale = locale).to_string(),
+        NavType::Flag => t!("navigation.flag", locale = locale).to_string(),
+        NavType::Overview => t!("navigation.overview", locale = locale).to_string(),
+        NavType::Pause => t!("navigation.pause", locale = locale).to_string(),
+        NavType::Submit => t!("navigation.submit", locale = locale).to_string(),
+        NavType::Open => t!("navigation.open", locale = locale).to_string(),
+        NavType::Practice => t!("navigation.practice", locale = locale).to_string(),
+        NavType::Retry => t!("navigation.retry", locale = locale).to_string(),
+        NavType::Toggle => t!("navigation.toggle", locale = locale).to_string(),
+        NavType::Order => t!("navigation.order", locale = locale).to_string(),
+        NavType::Create => t!("navigation.create", locale = locale).to_string(),
+        NavType::Filter => t!("navigation.filter", locale = locale).to_string(),
     }
 }
 
@@ -52,6 +76,18 @@ fn get_shortcut(nav: &NavType) -> &str {
         NavType::Rerun => "r",
         NavType::Results => "ENTER",
         NavType::Import => "i",
+        NavType::History => "h",
+        NavType::Flag => "f",
+        NavType::Overview => "TAB",
+        NavType::Pause => "p",
+        NavType::Submit => "s",
+        NavType::Open => "ENTER",
+        NavType::Practice => "p",
+        NavType::Retry => "w",
+        NavType::Toggle => "SPACE",
+        NavType::Order => "o",
+        NavType::Create => "ENTER",
+        NavType::Filter => "g",
     }
 }
 
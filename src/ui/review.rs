@@ -0,0 +1,123 @@
+use crate::models::test::{AnswerModel, QuestionKind};
+use crate::models::scoring::Grade;
+use crate::models::matching::MatchKind;
+use super::layout;
+
+use tui::{
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+};
+use rust_i18n::t;
+
+// the given and correct answers of a question, with the explanation when there is one
+pub fn get_answer_spans(q: &AnswerModel, locale: &str) -> Vec<Spans<'static>> {
+    let mut spans = match q.kind {
+        QuestionKind::Typed => get_typed_spans(q, locale),
+        QuestionKind::Cloze => get_gap_spans(q, locale),
+        _ => get_choice_spans(q),
+    };
+    if q.timed_out {
+        spans.push(Spans::from(Span::raw("")));
+        spans.push(Spans::from(Span::styled(
+            t!("results.timeout", locale = locale).to_string(),
+            Style::default().fg(Color::Yellow).add_modifier(Modifier::ITALIC))));
+    }
+    if !q.explanation.is_empty() {
+        spans.push(Spans::from(Span::raw("")));
+        spans.push(Spans::from(Span::styled(
+            t!("results.explanation", locale = locale).to_string(),
+            Style::default().add_modifier(Modifier::BOLD))));
+        spans.push(Spans::from(Span::raw(q.explanation.clone())));
+    }
+    spans
+}
+
+// points with the percent, then pass or fail and the grade when the test has them
+pub fn get_grade_spans(grade: &Grade, locale: &str) -> Vec<Spans<'static>> {
+    let mut spans = vec![Span::styled(
+        t!("summary.points", locale = locale,
+            points = format_points(grade.points), max = format_points(grade.max_points),
+            percent = format!("{:.0}", grade.percent())).to_string(),
+        Style::default().add_modifier(Modifier::BOLD))];
+    match grade.passed {
+        Some(true) => spans.push(Span::styled(format!("  {}", t!("summary.passed", locale = locale)), Style::default().fg(Color::Green))),
+        Some(false) => spans.push(Span::styled(format!("  {}", t!("summary.failed", locale = locale)), Style::default().fg(Color::Red))),
+        None => {},
+    }
+    if let Some(g) = grade.grade.as_ref() {
+        spans.push(Span::raw(format!("  {}", t!("summary.grade", locale = locale, grade = g))));
+    }
+    vec![Spans::from(spans)]
+}
+
+// whole points without decimals, weights and negative marking can give fractions
+fn format_points(points: f64) -> String {
+    format!("{:.2}", points).trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+fn get_choice_spans(q: &AnswerModel) -> Vec<Spans<'static>> {
+    q.answers.iter()
+        .enumerate()
+        .map(|(aidx, a)| {
+            let mut color = Color::Black;
+            if q.given.contains(&aidx) {
+                color = Color::Red;
+            }
+            if q.correct.contains(&aidx) {
+                color = Color::Green;
+            }
+            let mut text = a.to_string();
+            if q.kind == QuestionKind::Multiple {
+                text = layout::get_checkbox_item(text, q.given.contains(&aidx));
+            }
+            Spans::from(Span::styled(
+                    text, 
+                    Style::default().bg(color).fg(Color::White)))
+        })
+        .collect::<Vec<Spans>>()
+}
+
+fn get_match_status(matched: &MatchKind, locale: &str) -> (String, Color) {
+    match matched {
+        MatchKind::Exact => (t!("results.typed.exact", locale = locale).to_string(), Color::Green),
+        MatchKind::Typo => (t!("results.typed.typo", locale = locale).to_string(), Color::Yellow),
+        MatchKind::Wrong => (t!("results.typed.wrong", locale = locale).to_string(), Color::Red),
+    }
+}
+
+// one line per gap with the given answer, its status and the correct answers
+fn get_gap_spans(q: &AnswerModel, locale: &str) -> Vec<Spans<'static>> {
+    q.gaps.iter()
+        .enumerate()
+        .map(|(gidx, g)| {
+            let (status, color) = get_match_status(&g.matched, locale);
+            let correct = g.gap.correct.iter()
+                .filter_map(|c| g.gap.answers.get(*c).cloned())
+                .collect::<Vec<String>>()
+                .join(" / ");
+            Spans::from(vec![
+                Span::raw(format!("{}. ", gidx + 1)),
+                Span::styled(g.text(), Style::default().bg(color).fg(Color::White)),
+                Span::raw(format!(" ({}) ", status)),
+                Span::styled(correct, Style::default().bg(Color::Green).fg(Color::White)),
+            ])
+        })
+        .collect::<Vec<Spans>>()
+}
+
+fn get_typed_spans(q: &AnswerModel, locale: &str) -> Vec<Spans<'static>> {
+    let (status, color) = get_match_status(&q.matched, locale);
+    let mut spans = vec![
+        Spans::from(vec![
+            Span::raw(format!("{} ", t!("results.typed.given", locale = locale))),
+            Span::styled(q.typed.clone(), Style::default().bg(color).fg(Color::White)),
+            Span::raw(format!(" ({})", status)),
+        ]),
+        Spans::from(Span::raw("")),
+        Spans::from(Span::raw(t!("results.typed.accepted", locale = locale).to_string())),
+    ];
+    q.answers.iter()
+        .map(|a| Spans::from(Span::styled(a.clone(), Style::default().bg(Color::Green).fg(Color::White))))
+        .for_each(|s| spans.push(s));
+    spans
+}